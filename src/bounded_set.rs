@@ -0,0 +1,79 @@
+use crate::{
+    queue::{Kind, Queue},
+    rr::RR,
+    Policy,
+};
+use std::{cmp::Eq, hash::Hash};
+
+// Boxing both caches keeps a set pointer-sized whichever policy it uses; inline, they differ by hundreds of bytes.
+enum Inner<K> {
+    Queue(Box<Queue<K, ()>>),
+    RR(Box<RR<K, ()>>),
+}
+
+/// The bounded set keeps at most `capacity` keys and evicts them by the chosen `Policy`,
+/// e.g. for remembering recently seen IDs. It reuses the cache eviction machinery with `()` values,
+/// which take no space in the underlying map.
+pub struct BoundedSet<K> {
+    inner: Inner<K>,
+}
+
+impl<K> BoundedSet<K>
+where
+    K: Eq + Hash + Copy,
+{
+    pub fn new(capacity: usize, policy: Policy) -> Self {
+        let inner = match policy {
+            Policy::FIFO => Inner::Queue(Box::new(Queue::new(capacity, Kind::FIFO))),
+            Policy::LIFO => Inner::Queue(Box::new(Queue::new(capacity, Kind::LIFO))),
+            Policy::RR => Inner::RR(Box::new(RR::new(capacity))),
+        };
+        Self { inner }
+    }
+
+    /// Adds the key, evicting another one if the set is full. Re-inserting a present key doesn't change its position.
+    // Time: O(1) | Space: O(n)
+    pub fn insert(&mut self, key: K) -> bool {
+        match &mut self.inner {
+            Inner::Queue(queue) => queue.set(key, ()),
+            Inner::RR(rr) => rr.set(key, ()),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn contains(&self, key: &K) -> bool {
+        match &self.inner {
+            Inner::Queue(queue) => queue.contains_key(key),
+            Inner::RR(rr) => rr.contains(key),
+        }
+    }
+
+    // Time: O(n) for FIFO and LIFO, O(1) for RR | Space: O(1)
+    pub fn remove(&mut self, key: &K) -> bool {
+        match &mut self.inner {
+            Inner::Queue(queue) => queue.remove(key),
+            Inner::RR(rr) => rr.remove(key),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        match &self.inner {
+            Inner::Queue(queue) => queue.capacity(),
+            Inner::RR(rr) => rr.capacity(),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        match &self.inner {
+            Inner::Queue(queue) => queue.len(),
+            Inner::RR(rr) => rr.len(),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    queue::{Kind, Queue},
+    rr::RR,
+    CachePolicy, Policy,
+};
+use std::{cmp::Eq, hash::Hash};
+
+/// A cache whose eviction policy is chosen at runtime, e.g. from a config value.
+/// Every operation dispatches to the wrapped policy cache, which stays reachable through the variants.
+/// The wrapped cache is boxed, so a `Cache` is the size of a pointer whichever policy it uses.
+pub enum Cache<K, V> {
+    Queue(Box<Queue<K, V>>),
+    RR(Box<RR<K, V>>),
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Eq + Hash + Copy,
+{
+    pub fn new(policy: Policy, capacity: usize) -> Self {
+        match policy {
+            Policy::FIFO => Cache::Queue(Box::new(Queue::new(capacity, Kind::FIFO))),
+            Policy::LIFO => Cache::Queue(Box::new(Queue::new(capacity, Kind::LIFO))),
+            Policy::RR => Cache::RR(Box::new(RR::new(capacity))),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn policy(&self) -> Policy {
+        match self {
+            Cache::Queue(queue) => match queue.kind() {
+                Kind::FIFO => Policy::FIFO,
+                Kind::LIFO => Policy::LIFO,
+            },
+            Cache::RR(_) => Policy::RR,
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        match self {
+            Cache::Queue(queue) => queue.get(key),
+            Cache::RR(rr) => rr.get(key),
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        match self {
+            Cache::Queue(queue) => queue.set(key, value),
+            Cache::RR(rr) => rr.set(key, value),
+        }
+    }
+
+    // Time: O(n) for FIFO and LIFO, O(1) for RR | Space: O(1)
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self {
+            Cache::Queue(queue) => queue.remove(key),
+            Cache::RR(rr) => rr.remove(key),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        match self {
+            Cache::Queue(queue) => queue.capacity(),
+            Cache::RR(rr) => rr.capacity(),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        match self {
+            Cache::Queue(queue) => queue.len(),
+            Cache::RR(rr) => rr.len(),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V> CachePolicy<K, V> for Cache<K, V>
+where
+    K: Eq + Hash + Copy,
+{
+    fn get(&mut self, key: &K) -> Option<&V> {
+        Cache::get(self, key)
+    }
+
+    fn set(&mut self, key: K, value: V) -> bool {
+        Cache::set(self, key, value)
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    clock::{Clock, SystemClock},
+    queue::{Kind, Queue},
+};
+use std::{
+    cmp::Eq,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// The debouncing cache holds writes back until a key has been quiet for the debounce window,
+/// so a key overwritten many times in quick succession is committed once, with its final value.
+/// Each `set` restarts the key's window. Settled writes are committed lazily by `set` and `get`,
+/// in the order they were written, and `flush` commits everything still pending.
+/// A window too large to represent as an `Instant`, e.g. `Duration::MAX`, never settles, so only `flush` commits.
+/// Lookups only see committed values. Committed entries are evicted by the wrapped `Queue`.
+pub struct DebouncingCache<K, V, C = SystemClock> {
+    committed: Queue<K, V>,
+    // The latest pending value of each key, with the sequence number of the write that set it.
+    pending: HashMap<K, (V, u64)>,
+    // Every pending write in write order. Writes superseded by a later one are skipped when popped.
+    writes: VecDeque<(K, Instant, u64)>,
+    next_seq: u64,
+    window: Duration,
+    clock: C,
+}
+
+impl<K, V> DebouncingCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(capacity: usize, kind: Kind, window: Duration) -> Self {
+        Self::with_clock(capacity, kind, window, SystemClock)
+    }
+}
+
+impl<K, V, C> DebouncingCache<K, V, C>
+where
+    K: Eq + Hash + Clone,
+    C: Clock,
+{
+    pub fn with_clock(capacity: usize, kind: Kind, window: Duration, clock: C) -> Self {
+        Self {
+            committed: Queue::new(capacity, kind),
+            pending: HashMap::new(),
+            writes: VecDeque::new(),
+            next_seq: 0,
+            window,
+            clock,
+        }
+    }
+
+    // Time: O(1) amortized | Space: O(w), where w is the number of writes within the window
+    pub fn set(&mut self, key: K, value: V) {
+        let now = self.clock.now();
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.pending.insert(key.clone(), (value, seq));
+        self.writes.push_back((key, now, seq));
+        self.commit_settled(now);
+    }
+
+    // Time: O(1) amortized | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let now = self.clock.now();
+        self.commit_settled(now);
+        self.committed.get(key)
+    }
+
+    /// Commits every pending write, settled or not, and returns how many were committed.
+    // Time: O(w) | Space: O(1)
+    pub fn flush(&mut self) -> usize {
+        let mut count = 0;
+        while let Some((key, _, seq)) = self.writes.pop_front() {
+            if self.commit(key, seq) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.committed.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.committed.is_empty()
+    }
+
+    // Pops writes off the front while their window has passed.
+    // A window that overflows `Instant` never passes, so such writes wait for `flush`.
+    // Time: O(1) amortized | Space: O(1)
+    fn commit_settled(&mut self, now: Instant) {
+        while let Some((_, written_at, _)) = self.writes.front() {
+            match written_at.checked_add(self.window) {
+                Some(settles_at) if now >= settles_at => (),
+                _ => break,
+            }
+            if let Some((key, _, seq)) = self.writes.pop_front() {
+                self.commit(key, seq);
+            }
+        }
+    }
+
+    // Commits the key's pending value if write `seq` is still its latest one.
+    // Time: O(1) | Space: O(1)
+    fn commit(&mut self, key: K, seq: u64) -> bool {
+        match self.pending.get(&key) {
+            Some((_, latest)) if *latest == seq => (),
+            _ => return false,
+        }
+        match self.pending.remove(&key) {
+            Some((value, _)) => {
+                self.committed.set(key, value);
+                true
+            }
+            None => false,
+        }
+    }
+}
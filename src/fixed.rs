@@ -0,0 +1,108 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::cmp::Eq;
+
+/// How a full `FixedCache` picks the slot to overwrite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eviction {
+    FIFO,
+    RR,
+}
+
+/// The fixed cache holds at most `N` entries in an inline array, with the capacity part of its type, and never allocates.
+/// Entries are evicted in FIFO order or at random. Lookups scan the slots linearly, which is cheap for the small `N` this is meant for.
+/// Seeding the generator with `with_eviction_seed` makes random eviction reproducible.
+pub struct FixedCache<K, V, const N: usize> {
+    slots: [Option<(K, V)>; N],
+    // Slot of the oldest entry.
+    head: usize,
+    len: usize,
+    eviction: Eviction,
+    // Created on the first random eviction unless a seed was given, so FIFO caches never touch the OS entropy source.
+    rng: Option<StdRng>,
+}
+
+impl<K, V, const N: usize> FixedCache<K, V, N>
+where
+    K: Eq,
+{
+    pub fn new() -> Self {
+        Self::with_eviction(Eviction::FIFO)
+    }
+
+    pub fn with_eviction(eviction: Eviction) -> Self {
+        Self {
+            slots: std::array::from_fn(|_| None),
+            head: 0,
+            len: 0,
+            eviction,
+            rng: None,
+        }
+    }
+
+    /// Like `with_eviction`, but with a seeded generator so random eviction is deterministic.
+    pub fn with_eviction_seed(eviction: Eviction, seed: u64) -> Self {
+        let mut fixed_cache = Self::with_eviction(eviction);
+        fixed_cache.rng = Some(StdRng::seed_from_u64(seed));
+        fixed_cache
+    }
+
+    // Time: O(N) | Space: O(1)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        if let Some((_, existing)) = self.slots.iter_mut().flatten().find(|(k, _)| *k == key) {
+            *existing = value;
+            return true;
+        }
+        if N == 0 {
+            return false;
+        }
+        if self.len < N {
+            self.slots[(self.head + self.len) % N] = Some((key, value));
+            self.len += 1;
+        } else {
+            match self.eviction {
+                Eviction::FIFO => {
+                    self.slots[self.head] = Some((key, value));
+                    self.head = (self.head + 1) % N;
+                }
+                Eviction::RR => {
+                    let rng = self.rng.get_or_insert_with(StdRng::from_entropy);
+                    self.slots[rng.gen_range(0..N)] = Some((key, value));
+                }
+            }
+        }
+        true
+    }
+
+    // Time: O(N) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.slots
+            .iter()
+            .flatten()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<K, V, const N: usize> Default for FixedCache<K, V, N>
+where
+    K: Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,100 @@
+use crate::queue::{Kind, Queue};
+use std::{borrow::Borrow, cmp::Eq, collections::HashMap, hash::Hash};
+
+/// The frozen cache is an immutable snapshot of another cache. It never evicts and can't be modified,
+/// so it is `Sync` whenever its keys and values are and can be shared between threads for lock-free reads.
+/// Iteration follows the order the source cache had when it was frozen.
+/// `thaw` turns it back into a mutable `Queue`.
+///
+/// There is no way to mutate a frozen cache:
+///
+/// ```compile_fail
+/// use rust_cache::queue::{Kind, Queue};
+///
+/// let mut queue = Queue::new(2, Kind::FIFO);
+/// queue.set(1, "one");
+/// let mut frozen = queue.into_frozen();
+/// frozen.set(2, "two");
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrozenCache<K, V> {
+    entry_map: HashMap<K, V>,
+    keys: Vec<K>,
+}
+
+impl<K, V> FrozenCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub(crate) fn from_entries<I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let entries = entries.into_iter();
+        let mut entry_map = HashMap::with_capacity(entries.size_hint().0);
+        let mut keys = Vec::with_capacity(entries.size_hint().0);
+        for (key, value) in entries {
+            keys.push(key.clone());
+            entry_map.insert(key, value);
+        }
+        Self { entry_map, keys }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry_map.get(key)
+    }
+
+    /// Same as `get`, since a frozen cache has no access order for a lookup to disturb.
+    // Time: O(1) | Space: O(1)
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry_map.contains_key(key)
+    }
+
+    // Time: O(n) | Space: O(1)
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.keys
+            .iter()
+            .filter_map(|key| self.entry_map.get_key_value(key))
+    }
+
+    /// Moves the entries into a new `Queue`, oldest first in the frozen order.
+    /// If `capacity` is below `len`, the entries the queue evicts on the way are dropped.
+    // Time: O(n) | Space: O(n)
+    pub fn thaw(mut self, capacity: usize, kind: Kind) -> Queue<K, V> {
+        let mut queue = Queue::new(capacity, kind);
+        for key in self.keys {
+            if let Some(value) = self.entry_map.remove(&key) {
+                queue.set(key, value);
+            }
+        }
+        queue
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+}
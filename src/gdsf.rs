@@ -0,0 +1,131 @@
+use std::{cmp::Eq, collections::HashMap, hash::Hash};
+
+struct Slot<V> {
+    value: V,
+    cost: f64,
+    size: usize,
+    frequency: u64,
+    priority: f64,
+    // Logical time of the last access, so ties go to the least recently used entry.
+    tick: u64,
+}
+
+/// The Greedy-Dual-Size-Frequency (GDSF) cache gives every entry the priority
+/// `frequency * cost / size + inflation` and evicts the entry with the lowest priority.
+/// On each eviction the inflation clock rises to the victim's priority, so entries that stop being accessed
+/// age relative to newly inserted or recently accessed ones, whose priority is recomputed on every access.
+/// Cheap-to-refetch and large entries go first, while costly small ones stay. A size of 0 counts as 1.
+pub struct GdsfCache<K, V> {
+    entry_map: HashMap<K, Slot<V>>,
+    capacity: usize,
+    inflation: f64,
+    tick: u64,
+}
+
+impl<K, V> GdsfCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entry_map: HashMap::with_capacity(capacity),
+            capacity,
+            inflation: 0.0,
+            tick: 0,
+        }
+    }
+
+    /// Inserts or replaces an entry. Replacing counts as an access, so it keeps and bumps the frequency.
+    // Time: O(1), O(n) when it evicts | Space: O(n)
+    pub fn set(&mut self, key: K, value: V, cost: f64, size: usize) -> bool {
+        if !self.entry_map.contains_key(&key) && self.entry_map.len() >= self.capacity {
+            match self.victim() {
+                Some(victim) => {
+                    if let Some(slot) = self.entry_map.remove(&victim) {
+                        self.inflation = slot.priority;
+                    }
+                }
+                None => return false,
+            }
+        }
+        let tick = self.next_tick();
+        let frequency = self.entry_map.get(&key).map_or(0, |slot| slot.frequency) + 1;
+        let mut slot = Slot {
+            value,
+            cost,
+            size: size.max(1),
+            frequency,
+            priority: 0.0,
+            tick,
+        };
+        slot.priority = Self::compute(&slot, self.inflation);
+        self.entry_map.insert(key, slot);
+        true
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let tick = self.next_tick();
+        let inflation = self.inflation;
+        let slot = self.entry_map.get_mut(key)?;
+        slot.frequency += 1;
+        slot.tick = tick;
+        slot.priority = Self::compute(slot, inflation);
+        Some(&slot.value)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn priority(&self, key: &K) -> Option<f64> {
+        self.entry_map.get(key).map(|slot| slot.priority)
+    }
+
+    /// The current value of the inflation clock, which is the priority of the last evicted entry.
+    // Time: O(1) | Space: O(1)
+    pub fn inflation(&self) -> f64 {
+        self.inflation
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entry_map.contains_key(key)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn compute(slot: &Slot<V>, inflation: f64) -> f64 {
+        slot.frequency as f64 * slot.cost / slot.size as f64 + inflation
+    }
+
+    // Lowest priority first, with the least recently accessed entry breaking ties.
+    // Time: O(n) | Space: O(1)
+    fn victim(&self) -> Option<K> {
+        self.entry_map
+            .iter()
+            .min_by(|(_, a), (_, b)| {
+                a.priority
+                    .total_cmp(&b.priority)
+                    .then_with(|| a.tick.cmp(&b.tick))
+            })
+            .map(|(key, _)| key.clone())
+    }
+}
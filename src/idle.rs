@@ -0,0 +1,196 @@
+use crate::clock::{Clock, SystemClock};
+use std::{
+    cmp::Eq,
+    collections::HashMap,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// The result of an `IdleCache::try_get` lookup.
+#[derive(Debug, PartialEq, Eq)]
+pub enum GetResult<V> {
+    /// The key is cached and live.
+    Hit(V),
+    /// The key was cached but went idle; the lookup removed it.
+    Expired,
+    /// The key isn't cached.
+    Missing,
+}
+
+struct Slot<V> {
+    value: V,
+    inserted_at: Instant,
+    last_access: Instant,
+}
+
+/// The idle cache evicts entries that haven't been accessed within `max_idle`,
+/// no matter how long ago they were inserted. Every `get` of a live entry resets its idle time.
+/// Idle entries are dropped lazily by `get`, or all at once by `purge_idle`.
+/// A `max_idle` too large to represent as an `Instant`, e.g. `Duration::MAX`, means entries never go idle.
+/// The number of entries is not bounded. Both timestamps of an entry can be queried for auditing.
+pub struct IdleCache<K, V, C = SystemClock> {
+    entry_map: HashMap<K, Slot<V>>,
+    max_idle: Duration,
+    clock: C,
+}
+
+impl<K, V> IdleCache<K, V>
+where
+    K: Eq + Hash,
+{
+    pub fn new(max_idle: Duration) -> Self {
+        Self::with_clock(max_idle, SystemClock)
+    }
+}
+
+impl<K, V, C> IdleCache<K, V, C>
+where
+    K: Eq + Hash,
+    C: Clock,
+{
+    pub fn with_clock(max_idle: Duration, clock: C) -> Self {
+        Self {
+            entry_map: HashMap::new(),
+            max_idle,
+            clock,
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) {
+        let now = self.clock.now();
+        self.entry_map.insert(
+            key,
+            Slot {
+                value,
+                inserted_at: now,
+                last_access: now,
+            },
+        );
+    }
+
+    /// Like `set`, but an unchanged live value is a no-op that keeps both timestamps.
+    // Time: O(1) | Space: O(n)
+    pub fn set_if_changed(&mut self, key: K, value: V)
+    where
+        V: PartialEq,
+    {
+        let now = self.clock.now();
+        let unchanged = match self.entry_map.get(&key) {
+            Some(slot) => !Self::is_idle(slot, self.max_idle, now) && slot.value == value,
+            None => false,
+        };
+        if !unchanged {
+            self.set(key, value);
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        match self.try_get(key) {
+            GetResult::Hit(value) => Some(value),
+            GetResult::Expired | GetResult::Missing => None,
+        }
+    }
+
+    /// Like `get`, but tells an idle entry, which it removes, apart from a key that was never cached.
+    // Time: O(1) | Space: O(1)
+    pub fn try_get(&mut self, key: &K) -> GetResult<&V> {
+        let now = self.clock.now();
+        let idle = match self.entry_map.get(key) {
+            Some(slot) => Self::is_idle(slot, self.max_idle, now),
+            None => return GetResult::Missing,
+        };
+        if idle {
+            self.entry_map.remove(key);
+            return GetResult::Expired;
+        }
+        match self.entry_map.get_mut(key) {
+            Some(slot) => {
+                slot.last_access = now;
+                GetResult::Hit(&slot.value)
+            }
+            None => GetResult::Missing,
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn is_idle(slot: &Slot<V>, max_idle: Duration, now: Instant) -> bool {
+        match slot.last_access.checked_add(max_idle) {
+            Some(idle_at) => now >= idle_at,
+            None => false,
+        }
+    }
+
+    /// When the live entry was last `set`. Idle entries report `None`, as they do for `get`.
+    // Time: O(1) | Space: O(1)
+    pub fn inserted_at(&self, key: &K) -> Option<Instant> {
+        self.live_slot(key).map(|slot| slot.inserted_at)
+    }
+
+    /// When the live entry was last `set` or returned by `get`. This doesn't count as an access itself.
+    /// Idle entries report `None`, as they do for `get`.
+    // Time: O(1) | Space: O(1)
+    pub fn last_accessed_at(&self, key: &K) -> Option<Instant> {
+        self.live_slot(key).map(|slot| slot.last_access)
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn live_slot(&self, key: &K) -> Option<&Slot<V>> {
+        let now = self.clock.now();
+        self.entry_map
+            .get(key)
+            .filter(|slot| !Self::is_idle(slot, self.max_idle, now))
+    }
+
+    /// Drops every entry that has been idle for `max_idle` or longer and returns how many were dropped.
+    // Time: O(n) | Space: O(1)
+    pub fn purge_idle(&mut self) -> usize {
+        let now = self.clock.now();
+        let max_idle = self.max_idle;
+        let before = self.entry_map.len();
+        self.entry_map
+            .retain(|_, slot| !Self::is_idle(slot, max_idle, now));
+        before - self.entry_map.len()
+    }
+
+    /// Returns the live entries with how long each has left before it goes idle, soonest first.
+    /// Already idle entries are left out, and entries that never go idle report `Duration::MAX`. This doesn't count as an access.
+    // Time: O(n log n) | Space: O(n)
+    pub fn entries_by_ttl(&self) -> Vec<(&K, &V, Duration)> {
+        let now = self.clock.now();
+        let mut entries: Vec<(&K, &V, Duration)> = self
+            .entry_map
+            .iter()
+            .filter_map(|(key, slot)| {
+                let remaining = match slot.last_access.checked_add(self.max_idle) {
+                    Some(idle_at) => idle_at
+                        .checked_duration_since(now)
+                        .filter(|remaining| !remaining.is_zero())?,
+                    None => Duration::MAX,
+                };
+                Some((key, &slot.value, remaining))
+            })
+            .collect();
+        entries.sort_by_key(|(_, _, remaining)| *remaining);
+        entries
+    }
+
+    /// Drops every idle entry, then iterates over the live ones in no particular order.
+    /// Iterating doesn't count as an access.
+    // Time: O(n) | Space: O(1)
+    pub fn iter_fresh(&mut self) -> impl Iterator<Item = (&K, &V)> {
+        self.purge_idle();
+        self.entry_map.iter().map(|(key, slot)| (key, &slot.value))
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+}
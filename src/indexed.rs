@@ -0,0 +1,92 @@
+use crate::{
+    queue::{Kind, Queue},
+    SetOutcome,
+};
+use std::{
+    cmp::Eq,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// The indexed cache is a `Queue` that also keeps a reverse index from each value to the keys holding it,
+/// so keys can be looked up by value. Inserts, updates, evictions and removals all keep the index in step.
+pub struct IndexedCache<K, V> {
+    entries: Queue<K, V>,
+    index: HashMap<V, HashSet<K>>,
+}
+
+impl<K, V> IndexedCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+{
+    pub fn new(capacity: usize, kind: Kind) -> Self {
+        Self {
+            entries: Queue::new(capacity, kind),
+            index: HashMap::new(),
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        let previous = self.entries.get_cloned(&key);
+        match self.entries.set_reporting(key.clone(), value.clone()) {
+            SetOutcome::Rejected => return false,
+            SetOutcome::Evicted(evicted_key, evicted_value) => {
+                self.unindex(&evicted_key, &evicted_value)
+            }
+            SetOutcome::Updated => {
+                if let Some(previous) = previous {
+                    self.unindex(&key, &previous);
+                }
+            }
+            SetOutcome::Inserted => (),
+        }
+        self.index.entry(value).or_default().insert(key);
+        true
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    // Time: O(n) | Space: O(1)
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self.entries.take(key) {
+            Some(value) => {
+                self.unindex(key, &value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the keys currently holding `value`, in no particular order.
+    // Time: O(k) | Space: O(k)
+    pub fn keys_for_value(&self, value: &V) -> Vec<&K> {
+        match self.index.get(value) {
+            Some(keys) => keys.iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn unindex(&mut self, key: &K, value: &V) {
+        if let Some(keys) = self.index.get_mut(value) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.index.remove(value);
+            }
+        }
+    }
+}
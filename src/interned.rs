@@ -0,0 +1,86 @@
+use crate::{
+    queue::{Kind, Queue},
+    SetOutcome,
+};
+use std::{cmp::Eq, collections::HashSet, hash::Hash, rc::Rc};
+
+/// The interned cache is a `Queue` that stores each distinct value once, for caches where many keys map to equal values.
+/// Entries share their value through an `Rc` from a pool, and a value leaves the pool when its last entry goes.
+pub struct InternedCache<K, V> {
+    entries: Queue<K, Rc<V>>,
+    pool: HashSet<Rc<V>>,
+}
+
+impl<K, V> InternedCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash,
+{
+    pub fn new(capacity: usize, kind: Kind) -> Self {
+        Self {
+            entries: Queue::new(capacity, kind),
+            pool: HashSet::new(),
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        let shared = match self.pool.get(&value) {
+            Some(shared) => Rc::clone(shared),
+            None => {
+                let shared = Rc::new(value);
+                self.pool.insert(Rc::clone(&shared));
+                shared
+            }
+        };
+        let previous = self.entries.get_cloned(&key);
+        let outcome = self.entries.set_reporting(key, Rc::clone(&shared));
+        drop(shared);
+        match outcome {
+            SetOutcome::Inserted => true,
+            SetOutcome::Updated => {
+                if let Some(previous) = previous {
+                    self.release(previous);
+                }
+                true
+            }
+            SetOutcome::Evicted(_, evicted) => {
+                self.release(evicted);
+                true
+            }
+            SetOutcome::Rejected => {
+                self.pool.retain(|shared| Rc::strong_count(shared) > 1);
+                false
+            }
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|shared| shared.as_ref())
+    }
+
+    /// How many distinct values are stored.
+    // Time: O(1) | Space: O(1)
+    pub fn unique_value_count(&self) -> usize {
+        self.pool.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Drops the value from the pool once `shared` and the pool hold the only references.
+    // Time: O(1) | Space: O(1)
+    fn release(&mut self, shared: Rc<V>) {
+        if Rc::strong_count(&shared) == 2 {
+            self.pool.remove(shared.as_ref());
+        }
+    }
+}
@@ -1,47 +1,2541 @@
+pub mod bounded_set;
+pub mod cache;
+pub mod clock;
+pub mod debouncing;
+pub mod fixed;
+pub mod frozen;
+pub mod gdsf;
+pub mod idle;
+pub mod indexed;
+pub mod interned;
+pub mod loading;
+pub mod lru_k;
+pub mod meta;
+pub mod negative;
+pub mod partitioned;
+pub mod priority;
+pub mod promotion;
 pub mod queue;
 pub mod rr;
+pub mod tiered;
+pub mod timed;
+pub mod trace;
+pub mod versioned;
+pub mod weighted;
+
+use std::{collections::HashSet, hash::Hash};
+
+/// The operations every bounded cache supports, so wrappers can work over any policy.
+pub trait CachePolicy<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V>;
+    fn set(&mut self, key: K, value: V) -> bool;
+}
+
+impl<K, V> CachePolicy<K, V> for queue::Queue<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    fn get(&mut self, key: &K) -> Option<&V> {
+        queue::Queue::get(self, key)
+    }
+
+    fn set(&mut self, key: K, value: V) -> bool {
+        queue::Queue::set(self, key, value)
+    }
+}
+
+impl<K, V> CachePolicy<K, V> for rr::RR<K, V>
+where
+    K: Eq + Hash + Copy,
+{
+    fn get(&mut self, key: &K) -> Option<&V> {
+        rr::RR::get(self, key)
+    }
+
+    fn set(&mut self, key: K, value: V) -> bool {
+        rr::RR::set(self, key, value)
+    }
+}
+
+/// The eviction policies a cache can be built with when the choice is made at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    FIFO,
+    LIFO,
+    RR,
+}
+
+/// How a cache turns a requested capacity into its entry bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapacityPolicy {
+    /// Hold at most exactly this many entries.
+    Exact(usize),
+    /// Hold at least this many entries, and as many more as the map allocated room for.
+    AtLeast(usize),
+}
+
+/// What a weighted cache's capacity counts, so an entry count can't be mistaken for a byte budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capacity {
+    /// Hold at most this many entries, whatever they weigh.
+    Entries(usize),
+    /// Hold entries up to this total weight, e.g. bytes as measured by the weigher, however many that is.
+    Bytes(usize),
+}
+
+/// Sent by a cache with a signal sender when its fill level crosses the capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheSignal {
+    /// `len` reached the capacity.
+    CapacityReached,
+    /// `len` dropped below the capacity after having reached it.
+    PressureRelieved,
+}
+
+/// Sent to the receivers returned by `Queue::subscribe` as the cache is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheEvent<K> {
+    /// A set stored a value for the key.
+    Insert(K),
+    /// A lookup found the key.
+    Hit(K),
+    /// A lookup found nothing. The key is left out because lookups may use a borrowed form of it.
+    Miss,
+    /// The key was evicted to make room.
+    Evict(K),
+}
+
+/// What a `set_reporting` call did to the cache.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SetOutcome<K, V> {
+    /// The key was new and there was room for it.
+    Inserted,
+    /// The key was already cached and its value was replaced.
+    Updated,
+    /// The key was new and the returned entry was evicted to make room.
+    Evicted(K, V),
+    /// The key was new and nothing could be evicted to make room, e.g. a zero capacity cache.
+    Rejected,
+}
+
+impl<K, V> SetOutcome<K, V> {
+    /// Whether the insert displaced another entry.
+    pub fn is_eviction(&self) -> bool {
+        matches!(self, SetOutcome::Evicted(_, _))
+    }
+
+    /// The displaced entry, if the insert evicted one.
+    pub fn into_evicted(self) -> Option<(K, V)> {
+        match self {
+            SetOutcome::Evicted(key, value) => Some((key, value)),
+            _ => None,
+        }
+    }
+}
+
+/// Compares two key snapshots and returns the keys added and removed between them, in no particular order.
+pub fn diff_keys<K>(old: &HashSet<K>, new: &HashSet<K>) -> (Vec<K>, Vec<K>)
+where
+    K: Eq + Hash + Clone,
+{
+    let added = new.difference(old).cloned().collect();
+    let removed = old.difference(new).cloned().collect();
+    (added, removed)
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::queue::{Kind, Queue};
-    use crate::rr::RR;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::num::NonZeroUsize;
+    use std::ops::ControlFlow;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::bounded_set::BoundedSet;
+    use crate::cache::Cache;
+    use crate::clock::{Clock, MockClock};
+    use crate::debouncing::DebouncingCache;
+    use crate::fixed::{Eviction, FixedCache};
+    use crate::frozen::FrozenCache;
+    use crate::gdsf::GdsfCache;
+    use crate::idle::{GetResult, IdleCache};
+    use crate::indexed::IndexedCache;
+    use crate::interned::InternedCache;
+    use crate::loading::LoadingCache;
+    use crate::lru_k::LruKCache;
+    use crate::meta::MetaCache;
+    use crate::negative::{Cached, NegativeCache};
+    use crate::partitioned::PartitionedCache;
+    use crate::priority::{PriorityCache, ScoredCache};
+    use crate::promotion::PromotionCache;
+    use crate::queue::{AutoGrow, Kind, Queue};
+    use crate::rr::{SampledRR, RR};
+    use crate::tiered::{Tier, TierStats, TieredCache};
+    use crate::timed::TimedCache;
+    use crate::trace::{replay, TraceOp, TracingCache};
+    use crate::versioned::VersionedCache;
+    use crate::weighted::WeightedCache;
+    use crate::{diff_keys, CacheEvent, CacheSignal, Capacity, CapacityPolicy, Policy, SetOutcome};
 
     #[test]
     fn random_replacement() {
         let mut rr_cache = RR::new(3);
-        assert_eq!(rr_cache.get(1), None);
-        assert_eq!(rr_cache.set(1, "one"), true);
-        assert_eq!(rr_cache.get(1), Some(&"one"));
+        assert_eq!(rr_cache.get(&1), None);
+        assert!(rr_cache.set(1, "one"));
+        assert_eq!(rr_cache.get(&1), Some(&"one"));
 
-        assert_eq!(rr_cache.set(2, "two"), true);
-        assert_eq!(rr_cache.set(3, "three"), true);
-        assert_eq!(rr_cache.set(4, "four"), true);
-        assert!(rr_cache.get(2).is_some());
+        assert!(rr_cache.set(2, "two"));
+        assert!(rr_cache.set(3, "three"));
+        assert!(rr_cache.set(4, "four"));
+        assert_eq!(rr_cache.get(&4), Some(&"four"));
+        let survivors = [1, 2, 3]
+            .into_iter()
+            .filter(|key| rr_cache.get(key).is_some())
+            .count();
+        assert_eq!(survivors, 2);
     }
 
     #[test]
     fn queue_fifo() {
         let mut fifo_cache: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
-        assert_eq!(fifo_cache.get(1), None);
-        assert_eq!(fifo_cache.set(1, "one"), true);
-        assert_eq!(fifo_cache.get(1), Some(&"one"));
+        assert_eq!(fifo_cache.get(&1), None);
+        assert!(fifo_cache.set(1, "one"));
+        assert_eq!(fifo_cache.get(&1), Some(&"one"));
 
-        assert_eq!(fifo_cache.set(2, "two"), true);
-        assert_eq!(fifo_cache.set(3, "three"), true);
-        assert_eq!(fifo_cache.set(4, "four"), true);
-        assert!(fifo_cache.get(2).is_some());
+        assert!(fifo_cache.set(2, "two"));
+        assert!(fifo_cache.set(3, "three"));
+        assert!(fifo_cache.set(4, "four"));
+        assert!(fifo_cache.get(&2).is_some());
     }
 
     #[test]
     fn queue_lifo() {
         let mut lifo_cache: Queue<i32, &str> = Queue::new(3, Kind::LIFO);
-        assert_eq!(lifo_cache.get(1), None);
-        assert_eq!(lifo_cache.set(1, "one"), true);
-        assert_eq!(lifo_cache.get(1), Some(&"one"));
-
-        assert_eq!(lifo_cache.set(2, "two"), true);
-        assert_eq!(lifo_cache.set(3, "three"), true);
-        assert_eq!(lifo_cache.set(4, "four"), true);
-        assert!(lifo_cache.get(2).is_some());
+        assert_eq!(lifo_cache.get(&1), None);
+        assert!(lifo_cache.set(1, "one"));
+        assert_eq!(lifo_cache.get(&1), Some(&"one"));
+
+        assert!(lifo_cache.set(2, "two"));
+        assert!(lifo_cache.set(3, "three"));
+        assert!(lifo_cache.set(4, "four"));
+        assert!(lifo_cache.get(&2).is_some());
+    }
+
+    #[test]
+    fn queue_get_cloned() {
+        let mut fifo_cache: Queue<i32, Vec<&str>> = Queue::new(2, Kind::FIFO);
+        assert_eq!(fifo_cache.get_cloned(&1), None);
+        assert!(fifo_cache.set(1, vec!["one"]));
+
+        let mut cloned = fifo_cache.get_cloned(&1).unwrap();
+        cloned.push("uno");
+        assert_eq!(fifo_cache.get(&1), Some(&vec!["one"]));
+        assert_eq!(cloned, vec!["one", "uno"]);
+    }
+
+    #[test]
+    fn random_replacement_get_cloned() {
+        let mut rr_cache = RR::new(2);
+        assert_eq!(rr_cache.get_cloned(&1), None);
+        assert!(rr_cache.set(1, String::from("one")));
+
+        let mut cloned = rr_cache.get_cloned(&1).unwrap();
+        cloned.push_str("-uno");
+        assert_eq!(rr_cache.get(&1), Some(&String::from("one")));
+        assert_eq!(cloned, "one-uno");
+    }
+
+    #[test]
+    fn queue_string_keys() {
+        let mut fifo_cache: Queue<String, i32> = Queue::new(3, Kind::FIFO);
+        assert_eq!(fifo_cache.get("one"), None);
+        assert!(fifo_cache.set(String::from("one"), 1));
+        assert!(fifo_cache.set(String::from("two"), 2));
+        assert!(fifo_cache.set(String::from("three"), 3));
+        assert_eq!(fifo_cache.get("one"), Some(&1));
+
+        assert!(fifo_cache.set(String::from("four"), 4));
+        assert_eq!(fifo_cache.get("one"), None);
+        assert_eq!(fifo_cache.get("two"), Some(&2));
+        assert_eq!(fifo_cache.get("four"), Some(&4));
+    }
+
+    #[test]
+    fn negative_cache() {
+        let clock = MockClock::new();
+        let mut negative_cache =
+            NegativeCache::with_clock(3, Duration::from_secs(10), clock.clone());
+        assert!(negative_cache.set(1, "one"));
+        assert!(negative_cache.set_absent(2));
+        assert_eq!(negative_cache.get(&1), Cached::Hit(&"one"));
+        assert_eq!(negative_cache.get(&2), Cached::Absent);
+        assert_eq!(negative_cache.get(&3), Cached::Unknown);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(negative_cache.get(&1), Cached::Hit(&"one"));
+        assert_eq!(negative_cache.get(&2), Cached::Unknown);
+
+        assert!(negative_cache.set_absent(3));
+        assert!(negative_cache.set_absent(4));
+        assert!(negative_cache.set_absent(5));
+        assert_eq!(negative_cache.get(&1), Cached::Unknown);
+        assert_eq!(negative_cache.get(&5), Cached::Absent);
+    }
+
+    #[test]
+    fn negative_cache_absent_forever() {
+        let clock = MockClock::new();
+        let mut negative_cache: NegativeCache<i32, &str, _> =
+            NegativeCache::with_clock(3, Duration::MAX, clock.clone());
+        assert!(negative_cache.set_absent(1));
+        clock.advance(Duration::from_secs(1_000_000));
+        assert_eq!(negative_cache.get(&1), Cached::Absent);
+    }
+
+    #[test]
+    fn queue_take() {
+        let mut fifo_cache: Queue<String, i32> = Queue::new(3, Kind::FIFO);
+        assert!(fifo_cache.set(String::from("token"), 1));
+        assert!(fifo_cache.set(String::from("other"), 2));
+
+        assert_eq!(fifo_cache.take(&String::from("token")), Some(1));
+        assert_eq!(fifo_cache.get("token"), None);
+        assert_eq!(fifo_cache.take(&String::from("token")), None);
+        assert_eq!(fifo_cache.take(&String::from("missing")), None);
+
+        assert!(fifo_cache.set(String::from("three"), 3));
+        assert!(fifo_cache.set(String::from("four"), 4));
+        assert!(fifo_cache.set(String::from("five"), 5));
+        assert_eq!(fifo_cache.get("other"), None);
+        assert_eq!(fifo_cache.get("three"), Some(&3));
+    }
+
+    #[test]
+    fn queue_reserve() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        assert!(fifo_cache.set(0, 0));
+        fifo_cache.reserve(8);
+        assert_eq!(fifo_cache.capacity(), 10);
+
+        for key in 1..10 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(fifo_cache.len(), 10);
+        assert!((0..10).all(|key| fifo_cache.get(&key) == Some(&key)));
+
+        assert!(fifo_cache.set(10, 10));
+        assert_eq!(fifo_cache.len(), 10);
+        assert_eq!(fifo_cache.get(&0), None);
+    }
+
+    #[test]
+    fn queue_reserve_unallocatable() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        fifo_cache.reserve(usize::MAX);
+        assert_eq!(fifo_cache.capacity(), usize::MAX);
+        assert!(fifo_cache.set(0, 0));
+        assert_eq!(fifo_cache.get(&0), Some(&0));
+    }
+
+    #[test]
+    fn random_replacement_reserve() {
+        let mut rr_cache = RR::new(2);
+        assert!(rr_cache.is_empty());
+        rr_cache.reserve(8);
+        assert_eq!(rr_cache.capacity(), 10);
+
+        for key in 0..10 {
+            assert!(rr_cache.set(key, key));
+        }
+        assert_eq!(rr_cache.len(), 10);
+        assert!((0..10).all(|key| rr_cache.get(&key) == Some(&key)));
+    }
+
+    #[test]
+    fn random_replacement_reserve_unallocatable() {
+        let mut rr_cache = RR::new(2);
+        rr_cache.reserve(usize::MAX);
+        assert_eq!(rr_cache.capacity(), usize::MAX);
+        assert!(rr_cache.set(0, 0));
+        assert_eq!(rr_cache.get(&0), Some(&0));
+    }
+
+    #[test]
+    fn random_replacement_contains() {
+        let mut rr_cache = RR::new(3);
+        assert!(rr_cache.set(1, "one"));
+        {
+            let probe = 1;
+            assert!(rr_cache.contains(&probe));
+        }
+        let missing = 2;
+        assert!(!rr_cache.contains(&missing));
+    }
+
+    #[test]
+    fn queue_set_reporting() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(2, Kind::FIFO);
+        assert_eq!(fifo_cache.set_reporting(1, "one"), SetOutcome::Inserted);
+        assert_eq!(fifo_cache.set_reporting(2, "two"), SetOutcome::Inserted);
+        assert_eq!(fifo_cache.set_reporting(1, "uno"), SetOutcome::Updated);
+        assert_eq!(fifo_cache.get(&2), Some(&"two"));
+        assert_eq!(
+            fifo_cache.set_reporting(3, "three"),
+            SetOutcome::Evicted(1, "uno")
+        );
+
+        let mut empty_cache: Queue<i32, &str> = Queue::new(0, Kind::LIFO);
+        assert_eq!(empty_cache.set_reporting(1, "one"), SetOutcome::Rejected);
+    }
+
+    #[test]
+    fn random_replacement_set_reporting() {
+        let mut rr_cache = RR::new(2);
+        assert_eq!(rr_cache.set_reporting(1, 10), SetOutcome::Inserted);
+        assert_eq!(rr_cache.set_reporting(2, 20), SetOutcome::Inserted);
+        assert_eq!(rr_cache.set_reporting(2, 22), SetOutcome::Updated);
+        match rr_cache.set_reporting(3, 30) {
+            SetOutcome::Evicted(key, value) => {
+                assert!(key == 1 && value == 10 || key == 2 && value == 22);
+                assert!(!rr_cache.contains(&key));
+            }
+            outcome => panic!("expected an eviction, got {:?}", outcome),
+        }
+        assert_eq!(rr_cache.len(), 2);
+    }
+
+    #[test]
+    fn queue_auto_grow() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        fifo_cache.set_auto_grow(Some(AutoGrow {
+            factor: 2,
+            max: 8,
+            threshold: 2,
+            window: 4,
+        }));
+        for key in 0..2 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(fifo_cache.capacity(), 2);
+
+        for key in 2..100 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(fifo_cache.capacity(), 8);
+        assert_eq!(fifo_cache.len(), 8);
+        assert!((92..100).all(|key| fifo_cache.get(&key) == Some(&key)));
+    }
+
+    #[test]
+    fn queue_auto_grow_zero_factor_and_threshold() {
+        let mut eager: Queue<i32, i32> = Queue::new(4, Kind::FIFO);
+        eager.set_auto_grow(Some(AutoGrow {
+            factor: 0,
+            max: usize::MAX,
+            threshold: 0,
+            window: 4,
+        }));
+        for key in 0..4 {
+            assert!(eager.set(key, key));
+        }
+        assert_eq!(eager.capacity(), 4);
+        assert!(eager.set(4, 4));
+        assert_eq!(eager.capacity(), 5);
+    }
+
+    #[test]
+    fn random_replacement_edge_capacities() {
+        let mut empty_cache = RR::new(0);
+        assert!(!empty_cache.set(1, "one"));
+        assert!(empty_cache.is_empty());
+        assert_eq!(empty_cache.get(&1), None);
+
+        let mut single_cache = RR::new(1);
+        for key in 0..100 {
+            assert!(single_cache.set(key, key));
+            assert_eq!(single_cache.len(), 1);
+            assert_eq!(single_cache.get(&key), Some(&key));
+        }
+    }
+
+    #[test]
+    fn random_replacement_remove_down_to_empty() {
+        let mut rr_cache = RR::new(4);
+        for key in 0..4 {
+            assert!(rr_cache.set(key, key));
+        }
+        for key in [2, 0, 3, 1] {
+            assert!(rr_cache.remove(&key));
+        }
+        assert_eq!(rr_cache.len(), 0);
+        assert!(rr_cache.is_empty());
+        assert!(!rr_cache.remove(&0));
+
+        for key in 10..20 {
+            assert!(rr_cache.set(key, key));
+            assert!(rr_cache.len() <= 4);
+        }
+        assert_eq!(rr_cache.len(), 4);
+        assert_eq!(
+            (10..20).filter(|key| rr_cache.get(key).is_some()).count(),
+            4
+        );
+    }
+
+    #[test]
+    fn queue_key_order() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
+        assert!(fifo_cache.key_order().is_empty());
+        assert!(fifo_cache.set(1, "one"));
+        assert!(fifo_cache.set(2, "two"));
+        assert!(fifo_cache.set(3, "three"));
+        assert!(fifo_cache.set(1, "uno"));
+        assert_eq!(fifo_cache.key_order(), vec![&1, &2, &3]);
+
+        assert!(fifo_cache.set(4, "four"));
+        assert_eq!(fifo_cache.key_order(), vec![&2, &3, &4]);
+    }
+
+    #[test]
+    fn queue_iter_sorted_by() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
+        assert!(fifo_cache.set(2, "b"));
+        assert!(fifo_cache.set(3, "a"));
+        assert!(fifo_cache.set(1, "c"));
+
+        let by_key = fifo_cache.iter_sorted_by(|a, b| a.0.cmp(b.0));
+        assert_eq!(by_key, vec![(&1, &"c"), (&2, &"b"), (&3, &"a")]);
+        let by_value = fifo_cache.iter_sorted_by(|a, b| a.1.cmp(b.1));
+        assert_eq!(by_value, vec![(&3, &"a"), (&2, &"b"), (&1, &"c")]);
+    }
+
+    #[test]
+    fn random_replacement_iter_sorted_by() {
+        let mut rr_cache = RR::new(3);
+        assert!(rr_cache.set(2, "b"));
+        assert!(rr_cache.set(3, "a"));
+        assert!(rr_cache.set(1, "c"));
+
+        let by_key = rr_cache.iter_sorted_by(|a, b| a.0.cmp(b.0));
+        assert_eq!(by_key, vec![(&1, &"c"), (&2, &"b"), (&3, &"a")]);
+        let by_value = rr_cache.iter_sorted_by(|a, b| a.1.cmp(b.1));
+        assert_eq!(by_value, vec![(&3, &"a"), (&2, &"b"), (&1, &"c")]);
+    }
+
+    #[test]
+    fn queue_from_map() {
+        let warm = HashMap::from([(1, "one"), (2, "two"), (3, "three")]);
+        let fifo_cache = Queue::from_map(warm, 4, Kind::FIFO);
+        assert_eq!(fifo_cache.len(), 3);
+        assert_eq!(fifo_cache.capacity(), 4);
+        assert_eq!(fifo_cache.get(&1), Some(&"one"));
+        assert_eq!(fifo_cache.get(&2), Some(&"two"));
+        assert_eq!(fifo_cache.get(&3), Some(&"three"));
+
+        let warm = HashMap::from([(1, "one"), (2, "two"), (3, "three")]);
+        let small_cache = Queue::from_map(warm, 2, Kind::LIFO);
+        assert_eq!(small_cache.len(), 2);
+    }
+
+    #[test]
+    fn priority_cache() {
+        let mut priority_cache = PriorityCache::new(3);
+        assert!(priority_cache.set("config", 1, 10));
+        assert!(priority_cache.set("thumbnail", 2, 1));
+        assert!(priority_cache.set("session", 3, 5));
+
+        assert!(priority_cache.set("avatar", 4, 1));
+        assert_eq!(priority_cache.get(&"thumbnail"), None);
+        assert_eq!(priority_cache.len(), 3);
+
+        assert!(priority_cache.set("banner", 5, 1));
+        assert_eq!(priority_cache.get(&"avatar"), None);
+        assert_eq!(priority_cache.get(&"banner"), Some(&5));
+
+        assert!(priority_cache.set("config", 6, 0));
+        assert_eq!(priority_cache.priority(&"config"), Some(&0));
+        assert!(priority_cache.set("footer", 7, 2));
+        assert_eq!(priority_cache.get(&"config"), None);
+        assert_eq!(priority_cache.get(&"session"), Some(&3));
+        assert_eq!(priority_cache.get(&"banner"), Some(&5));
+    }
+
+    #[test]
+    fn queue_pin() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(3, Kind::FIFO);
+        assert!(fifo_cache.set(0, 0));
+        assert!(fifo_cache.pin(&0));
+        assert!(!fifo_cache.pin(&42));
+        for key in 1..50 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(fifo_cache.get(&0), Some(&0));
+        assert_eq!(fifo_cache.key_order(), vec![&0, &48, &49]);
+
+        let mut lifo_cache: Queue<i32, i32> = Queue::new(2, Kind::LIFO);
+        assert!(lifo_cache.set(1, 1));
+        assert!(lifo_cache.set(2, 2));
+        assert!(lifo_cache.pin(&1));
+        assert!(lifo_cache.pin(&2));
+        assert!(!lifo_cache.set(3, 3));
+        assert!(lifo_cache.set(2, 22));
+        assert!(lifo_cache.unpin(&2));
+        assert!(!lifo_cache.unpin(&2));
+        assert!(lifo_cache.set(3, 3));
+        assert_eq!(lifo_cache.get(&1), Some(&1));
+        assert_eq!(lifo_cache.get(&2), None);
+    }
+
+    #[test]
+    fn random_replacement_pin() {
+        let mut rr_cache = RR::new(3);
+        assert!(rr_cache.set(0, 0));
+        assert!(rr_cache.pin(&0));
+        assert!(!rr_cache.pin(&42));
+        for key in 1..200 {
+            assert!(rr_cache.set(key, key));
+        }
+        assert_eq!(rr_cache.get(&0), Some(&0));
+        assert_eq!(rr_cache.len(), 3);
+
+        let mut rr_cache = RR::new(2);
+        assert!(rr_cache.set(1, 1));
+        assert!(rr_cache.set(2, 2));
+        assert!(rr_cache.pin(&1));
+        assert!(rr_cache.pin(&2));
+        assert!(!rr_cache.set(3, 3));
+        assert!(rr_cache.unpin(&2));
+        assert!(!rr_cache.unpin(&2));
+        assert!(rr_cache.set(3, 3));
+        assert_eq!(rr_cache.get(&1), Some(&1));
+        assert_eq!(rr_cache.get(&2), None);
+    }
+
+    #[test]
+    fn queue_key_snapshot_diff() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
+        assert!(fifo_cache.set(1, "one"));
+        assert!(fifo_cache.set(2, "two"));
+        let before = fifo_cache.key_snapshot();
+
+        assert!(fifo_cache.set(3, "three"));
+        assert!(fifo_cache.set(4, "four"));
+        assert!(fifo_cache.remove(&2));
+        let after = fifo_cache.key_snapshot();
+
+        let (mut added, mut removed) = diff_keys(&before, &after);
+        added.sort();
+        removed.sort();
+        assert_eq!(added, vec![3, 4]);
+        assert_eq!(removed, vec![1, 2]);
+        assert_eq!(diff_keys(&after, &after), (vec![], vec![]));
+    }
+
+    #[test]
+    fn random_replacement_key_snapshot() {
+        let mut rr_cache = RR::new(2);
+        assert!(rr_cache.set(1, "one"));
+        assert!(rr_cache.set(2, "two"));
+        let before = rr_cache.key_snapshot();
+
+        assert!(rr_cache.set(3, "three"));
+        let (added, removed) = diff_keys(&before, &rr_cache.key_snapshot());
+        assert_eq!(added, vec![3]);
+        assert_eq!(removed.len(), 1);
+        assert!(!rr_cache.contains(&removed[0]));
+    }
+
+    #[test]
+    fn idle_cache() {
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::from_secs(10), clock.clone());
+        idle_cache.set("hot", 1);
+        idle_cache.set("cold", 2);
+        idle_cache.set("neglected", 3);
+
+        for _ in 0..5 {
+            clock.advance(Duration::from_secs(6));
+            assert_eq!(idle_cache.get(&"hot"), Some(&1));
+        }
+        assert_eq!(idle_cache.get(&"cold"), None);
+        assert_eq!(idle_cache.len(), 2);
+
+        assert_eq!(idle_cache.purge_idle(), 1);
+        assert_eq!(idle_cache.len(), 1);
+        assert_eq!(idle_cache.get(&"hot"), Some(&1));
+    }
+
+    #[test]
+    fn idle_cache_timestamps() {
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::from_secs(10), clock.clone());
+        let start = clock.now();
+        idle_cache.set("key", 1);
+        assert_eq!(idle_cache.inserted_at(&"key"), Some(start));
+        assert_eq!(idle_cache.last_accessed_at(&"key"), Some(start));
+        assert_eq!(idle_cache.inserted_at(&"missing"), None);
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(idle_cache.get(&"key"), Some(&1));
+        assert_eq!(idle_cache.inserted_at(&"key"), Some(start));
+        assert_eq!(
+            idle_cache.last_accessed_at(&"key"),
+            Some(start + Duration::from_secs(3))
+        );
+
+        clock.advance(Duration::from_secs(4));
+        idle_cache.set("key", 2);
+        assert_eq!(
+            idle_cache.inserted_at(&"key"),
+            Some(start + Duration::from_secs(7))
+        );
+        assert_eq!(
+            idle_cache.last_accessed_at(&"key"),
+            Some(start + Duration::from_secs(7))
+        );
+    }
+
+    #[test]
+    fn idle_cache_timestamps_of_idle_entries() {
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::from_secs(10), clock.clone());
+        idle_cache.set("key", 1);
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(idle_cache.inserted_at(&"key"), None);
+        assert_eq!(idle_cache.last_accessed_at(&"key"), None);
+        assert_eq!(idle_cache.len(), 1);
+    }
+
+    #[test]
+    fn idle_cache_iter_fresh() {
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::from_secs(10), clock.clone());
+        idle_cache.set("stale", 1);
+        idle_cache.set("live", 2);
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(idle_cache.get(&"live"), Some(&2));
+        idle_cache.set("fresh", 3);
+        clock.advance(Duration::from_secs(4));
+
+        let mut fresh: Vec<_> = idle_cache.iter_fresh().collect();
+        fresh.sort();
+        assert_eq!(fresh, vec![(&"fresh", &3), (&"live", &2)]);
+        assert_eq!(idle_cache.len(), 2);
+        assert_eq!(idle_cache.inserted_at(&"stale"), None);
+    }
+
+    #[test]
+    fn idle_cache_entries_by_ttl() {
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::from_secs(10), clock.clone());
+        idle_cache.set("first", 1);
+        clock.advance(Duration::from_secs(2));
+        idle_cache.set("second", 2);
+        clock.advance(Duration::from_secs(3));
+        idle_cache.set("third", 3);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(idle_cache.get(&"first"), Some(&1));
+        clock.advance(Duration::from_secs(6));
+
+        assert_eq!(
+            idle_cache.entries_by_ttl(),
+            vec![
+                (&"third", &3, Duration::from_secs(3)),
+                (&"first", &1, Duration::from_secs(4)),
+            ]
+        );
+        assert_eq!(idle_cache.len(), 3);
+    }
+
+    #[test]
+    fn set_if_changed() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
+        fifo_cache.set_update_moves_to_back(true);
+        for (key, value) in [(1, "one"), (2, "two"), (3, "three")] {
+            assert!(fifo_cache.set(key, value));
+        }
+        assert!(fifo_cache.set_if_changed(1, "one"));
+        assert_eq!(fifo_cache.key_order(), vec![&1, &2, &3]);
+        assert!(fifo_cache.set_if_changed(1, "uno"));
+        assert_eq!(fifo_cache.key_order(), vec![&2, &3, &1]);
+        let events = fifo_cache.subscribe();
+        assert!(fifo_cache.set(4, "four"));
+        assert!(fifo_cache.last_set_evicted());
+        assert!(fifo_cache.set_if_changed(4, "four"));
+        assert!(!fifo_cache.last_set_evicted());
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![CacheEvent::Evict(2), CacheEvent::Insert(4)]
+        );
+
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::from_secs(10), clock.clone());
+        let start = clock.now();
+        idle_cache.set_if_changed("key", 1);
+        clock.advance(Duration::from_secs(5));
+        idle_cache.set_if_changed("key", 1);
+        assert_eq!(idle_cache.inserted_at(&"key"), Some(start));
+        assert_eq!(idle_cache.last_accessed_at(&"key"), Some(start));
+        idle_cache.set_if_changed("key", 2);
+        assert_eq!(idle_cache.inserted_at(&"key"), Some(clock.now()));
+    }
+
+    #[test]
+    fn idle_cache_try_get() {
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::from_secs(10), clock.clone());
+        idle_cache.set("session", 1);
+        assert_eq!(idle_cache.try_get(&"session"), GetResult::Hit(&1));
+        assert_eq!(idle_cache.try_get(&"never"), GetResult::Missing);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(idle_cache.try_get(&"session"), GetResult::Expired);
+        assert_eq!(idle_cache.try_get(&"session"), GetResult::Missing);
+        assert!(idle_cache.is_empty());
+    }
+
+    #[test]
+    fn idle_cache_never_idle() {
+        let clock = MockClock::new();
+        let mut idle_cache = IdleCache::with_clock(Duration::MAX, clock.clone());
+        let start = clock.now();
+        idle_cache.set("key", 1);
+        clock.advance(Duration::from_secs(1_000_000));
+        assert_eq!(idle_cache.get(&"key"), Some(&1));
+        assert_eq!(idle_cache.try_get(&"key"), GetResult::Hit(&1));
+        idle_cache.set_if_changed("key", 1);
+        assert_eq!(idle_cache.inserted_at(&"key"), Some(start));
+        assert_eq!(
+            idle_cache.entries_by_ttl(),
+            vec![(&"key", &1, Duration::MAX)]
+        );
+        assert_eq!(idle_cache.purge_idle(), 0);
+        assert_eq!(idle_cache.len(), 1);
+    }
+
+    #[test]
+    fn debouncing_cache_commits_final_value() {
+        let clock = MockClock::new();
+        let mut cache =
+            DebouncingCache::with_clock(4, Kind::FIFO, Duration::from_millis(100), clock.clone());
+        for value in 1..=5 {
+            cache.set("key", value);
+            clock.advance(Duration::from_millis(40));
+        }
+        assert_eq!(cache.get(&"key"), None);
+        assert_eq!(cache.pending_len(), 1);
+
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(cache.get(&"key"), Some(&5));
+        assert_eq!(cache.pending_len(), 0);
+        assert_eq!(cache.len(), 1);
+
+        cache.set("key", 6);
+        cache.set("other", 1);
+        assert_eq!(cache.get(&"key"), Some(&5));
+        assert_eq!(cache.flush(), 2);
+        assert_eq!(cache.get(&"key"), Some(&6));
+        assert_eq!(cache.get(&"other"), Some(&1));
+        assert_eq!(cache.flush(), 0);
+    }
+
+    #[test]
+    fn debouncing_cache_commits_in_write_order() {
+        let clock = MockClock::new();
+        let mut ordered =
+            DebouncingCache::with_clock(2, Kind::FIFO, Duration::from_millis(100), clock.clone());
+        for key in 0..3 {
+            ordered.set(key, key);
+        }
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(ordered.get(&0), None);
+        assert_eq!(ordered.get(&1), Some(&1));
+        assert_eq!(ordered.get(&2), Some(&2));
+    }
+
+    #[test]
+    fn debouncing_cache_unbounded_window() {
+        let clock = MockClock::new();
+        let mut cache = DebouncingCache::with_clock(2, Kind::FIFO, Duration::MAX, clock.clone());
+        cache.set("key", 1);
+        clock.advance(Duration::from_secs(1_000_000));
+        assert_eq!(cache.get(&"key"), None);
+        assert_eq!(cache.pending_len(), 1);
+        assert_eq!(cache.flush(), 1);
+        assert_eq!(cache.get(&"key"), Some(&1));
+    }
+
+    #[test]
+    fn timed_cache_expires_at_ttl() {
+        let clock = MockClock::new();
+        let mut cache = TimedCache::with_clock(Duration::from_secs(10), clock.clone());
+        for key in 0..1_000 {
+            cache.set(key, key * 2);
+        }
+        assert_eq!(cache.len(), 1_000);
+        assert_eq!(cache.get(&999), Some(&1998));
+
+        clock.advance(Duration::from_secs(5));
+        cache.set(0, 1);
+        assert_eq!(cache.get(&500), Some(&1000));
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.get(&500), None);
+        assert_eq!(cache.get(&0), Some(&1));
+        assert_eq!(cache.len(), 1_000);
+
+        assert_eq!(cache.purge_expired(), 999);
+        assert_eq!(cache.len(), 1);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.get(&0), None);
+        assert_eq!(cache.purge_expired(), 1);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn timed_cache_never_expires() {
+        let clock = MockClock::new();
+        let mut forever = TimedCache::with_clock(Duration::MAX, clock.clone());
+        forever.set("key", 1);
+        clock.advance(Duration::from_secs(1_000_000));
+        assert_eq!(forever.get(&"key"), Some(&1));
+        assert_eq!(forever.purge_expired(), 0);
+    }
+
+    #[test]
+    fn fixed_cache() {
+        let mut fixed_cache: FixedCache<u32, u32, 4> = FixedCache::new();
+        assert_eq!(fixed_cache.capacity(), 4);
+        assert_eq!(fixed_cache.get(&1), None);
+        for key in 1..=4 {
+            assert!(fixed_cache.set(key, key * 10));
+        }
+        assert_eq!(fixed_cache.len(), 4);
+        assert!(fixed_cache.set(2, 22));
+        assert_eq!(fixed_cache.get(&2), Some(&22));
+
+        assert!(fixed_cache.set(5, 50));
+        assert!(fixed_cache.set(6, 60));
+        assert_eq!(fixed_cache.len(), 4);
+        assert_eq!(fixed_cache.get(&1), None);
+        assert_eq!(fixed_cache.get(&2), None);
+        assert_eq!(fixed_cache.get(&3), Some(&30));
+        assert_eq!(fixed_cache.get(&6), Some(&60));
+
+        let mut empty_cache: FixedCache<u32, u32, 0> = FixedCache::new();
+        assert!(!empty_cache.set(1, 1));
+    }
+
+    #[test]
+    fn queue_try_reserve() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        assert!(fifo_cache.try_reserve(3).is_ok());
+        assert_eq!(fifo_cache.capacity(), 5);
+
+        assert!(fifo_cache.try_reserve(usize::MAX).is_err());
+        assert_eq!(fifo_cache.capacity(), 5);
+    }
+
+    #[test]
+    fn random_replacement_try_reserve() {
+        let mut rr_cache: RR<i32, i32> = RR::new(2);
+        assert!(rr_cache.try_reserve(3).is_ok());
+        assert_eq!(rr_cache.capacity(), 5);
+
+        assert!(rr_cache.try_reserve(usize::MAX).is_err());
+        assert_eq!(rr_cache.capacity(), 5);
+    }
+
+    #[test]
+    fn queue_set_many_evicting() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(2, Kind::FIFO);
+        assert!(fifo_cache.set(1, "one"));
+        let evicted =
+            fifo_cache.set_many_evicting(vec![(2, "two"), (3, "three"), (4, "four"), (5, "five")]);
+        assert_eq!(evicted, vec![(1, "one"), (2, "two"), (3, "three")]);
+        assert_eq!(fifo_cache.key_order(), vec![&4, &5]);
+    }
+
+    #[test]
+    fn random_replacement_set_many_evicting() {
+        let mut rr_cache = RR::new(2);
+        let evicted = rr_cache.set_many_evicting((0..10).map(|key| (key, key)));
+        assert_eq!(evicted.len(), 8);
+        assert!(evicted.iter().all(|(key, _)| !rr_cache.contains(key)));
+        assert_eq!(rr_cache.len(), 2);
+    }
+
+    #[test]
+    fn indexed_cache() {
+        let mut indexed_cache = IndexedCache::new(3, Kind::FIFO);
+        assert!(indexed_cache.set("alice", "admin"));
+        assert!(indexed_cache.set("bob", "admin"));
+        assert!(indexed_cache.set("carol", "viewer"));
+
+        let mut admins = indexed_cache.keys_for_value(&"admin");
+        admins.sort();
+        assert_eq!(admins, vec![&"alice", &"bob"]);
+
+        assert!(indexed_cache.set("bob", "viewer"));
+        assert_eq!(indexed_cache.keys_for_value(&"admin"), vec![&"alice"]);
+
+        assert!(indexed_cache.set("dave", "editor"));
+        assert!(indexed_cache.keys_for_value(&"admin").is_empty());
+        let mut viewers = indexed_cache.keys_for_value(&"viewer");
+        viewers.sort();
+        assert_eq!(viewers, vec![&"bob", &"carol"]);
+
+        assert!(indexed_cache.remove(&"carol"));
+        assert_eq!(indexed_cache.keys_for_value(&"viewer"), vec![&"bob"]);
+        assert_eq!(indexed_cache.len(), 2);
+    }
+
+    #[test]
+    fn random_replacement_compact() {
+        let mut rr_cache = RR::new(3);
+        for key in 0..3 {
+            assert!(rr_cache.set(key, key));
+        }
+        for key in 3..200 {
+            rr_cache.compact();
+            match rr_cache.set_reporting(key, key) {
+                SetOutcome::Evicted(evicted_key, _) => assert!(!rr_cache.contains(&evicted_key)),
+                outcome => panic!("expected an eviction, got {:?}", outcome),
+            }
+            assert_eq!(rr_cache.len(), 3);
+        }
+        rr_cache.compact();
+        assert_eq!(rr_cache.key_snapshot().len(), 3);
+        assert_eq!(rr_cache.get(&199), Some(&199));
+    }
+
+    #[test]
+    fn random_replacement_compact_reindexes_slots() {
+        let mut rr_cache = RR::with_seed(16, 3);
+        for key in 0..200 {
+            assert!(rr_cache.set(key, key));
+            if key % 3 == 0 {
+                rr_cache.remove(&(key / 2));
+            }
+        }
+        rr_cache.compact();
+
+        let (keys, indices) = rr_cache.slots();
+        assert_eq!(keys.len(), rr_cache.len());
+        assert_eq!(indices.len(), rr_cache.len());
+        for (key, idx) in indices {
+            assert_eq!(keys.get(idx), Some(key));
+        }
+    }
+
+    #[test]
+    fn random_replacement_repeated_eviction() {
+        let mut rr_cache = RR::new(3);
+        for key in 0..3 {
+            assert!(rr_cache.set(key, key));
+        }
+        for key in 3..1_000 {
+            match rr_cache.set_reporting(key, key) {
+                SetOutcome::Evicted(evicted_key, _) => assert!(!rr_cache.contains(&evicted_key)),
+                outcome => panic!("expected an eviction, got {:?}", outcome),
+            }
+            assert_eq!(rr_cache.len(), 3);
+            assert_eq!(rr_cache.get(&key), Some(&key));
+        }
+    }
+
+    #[test]
+    fn set_outcome_eviction() {
+        let mut lifo_cache: Queue<i32, &str> = Queue::new(2, Kind::LIFO);
+        let inserted = lifo_cache.set_reporting(1, "one");
+        assert!(!inserted.is_eviction());
+        assert_eq!(inserted.into_evicted(), None);
+        assert_eq!(lifo_cache.set_reporting(2, "two").into_evicted(), None);
+        assert_eq!(lifo_cache.set_reporting(2, "dos").into_evicted(), None);
+
+        let over_capacity = lifo_cache.set_reporting(3, "three");
+        assert!(over_capacity.is_eviction());
+        assert_eq!(over_capacity.into_evicted(), Some((2, "dos")));
+    }
+
+    #[test]
+    fn queue_warm() {
+        let mut fifo_cache: Queue<&str, i32> = Queue::new(3, Kind::FIFO);
+        fifo_cache.warm(vec![
+            ("low", 1, 1),
+            ("top", 2, 100),
+            ("mid", 3, 50),
+            ("lowest", 4, 0),
+            ("high", 5, 75),
+        ]);
+        assert_eq!(fifo_cache.len(), 3);
+        assert_eq!(fifo_cache.get("low"), None);
+        assert_eq!(fifo_cache.get("lowest"), None);
+        assert_eq!(fifo_cache.key_order(), vec![&"mid", &"high", &"top"]);
+
+        assert!(fifo_cache.set("new", 6));
+        assert_eq!(fifo_cache.get("mid"), None);
+        assert_eq!(fifo_cache.get("top"), Some(&2));
+    }
+
+    #[test]
+    fn tiered_cache() {
+        let mut tiered_cache = TieredCache::new(2, 4);
+        assert!(tiered_cache.set("hot", 0));
+        for key in ["a", "b", "c"] {
+            assert!(tiered_cache.set(key, 1));
+            assert_eq!(tiered_cache.get(&"hot"), Some(&0));
+        }
+        assert_eq!(tiered_cache.tier(&"hot"), Some(Tier::L1));
+        assert_eq!(tiered_cache.tier(&"a"), Some(Tier::L2));
+        assert_eq!(tiered_cache.tier(&"b"), Some(Tier::L2));
+        assert_eq!(tiered_cache.tier(&"c"), Some(Tier::L1));
+
+        assert_eq!(tiered_cache.get(&"a"), Some(&1));
+        assert_eq!(tiered_cache.tier(&"a"), Some(Tier::L1));
+        assert_eq!(tiered_cache.tier(&"hot"), Some(Tier::L1));
+        assert_eq!(tiered_cache.tier(&"c"), Some(Tier::L2));
+        assert_eq!(tiered_cache.get(&"missing"), None);
+        assert_eq!(tiered_cache.len(), 4);
+        assert_eq!(
+            tiered_cache.stats(),
+            TierStats {
+                l1_hits: 3,
+                l2_hits: 1,
+                misses: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn tiered_cache_without_l1() {
+        let mut tiered_cache = TieredCache::new(0, 2);
+        assert!(tiered_cache.set("key", 1));
+        assert_eq!(tiered_cache.tier(&"key"), Some(Tier::L2));
+        assert_eq!(tiered_cache.get(&"key"), Some(&1));
+        assert_eq!(tiered_cache.get(&"key"), Some(&1));
+        assert!(tiered_cache.set("key", 2));
+        assert_eq!(tiered_cache.get(&"key"), Some(&2));
+        assert_eq!(tiered_cache.len(), 1);
+        assert_eq!(
+            tiered_cache.stats(),
+            TierStats {
+                l1_hits: 0,
+                l2_hits: 3,
+                misses: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn queue_evict_front_and_back() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(4, Kind::FIFO);
+        assert_eq!(fifo_cache.evict_front(), None);
+        for (key, value) in [(1, "one"), (2, "two"), (3, "three"), (4, "four")] {
+            assert!(fifo_cache.set(key, value));
+        }
+
+        assert_eq!(fifo_cache.evict_front(), Some((1, "one")));
+        assert_eq!(fifo_cache.evict_back(), Some((4, "four")));
+        assert_eq!(fifo_cache.get(&1), None);
+        assert_eq!(fifo_cache.get(&4), None);
+        assert_eq!(fifo_cache.len(), 2);
+
+        assert!(fifo_cache.pin(&3));
+        assert_eq!(fifo_cache.evict_back(), Some((2, "two")));
+        assert_eq!(fifo_cache.evict_back(), None);
+        assert_eq!(fifo_cache.key_order(), vec![&3]);
+    }
+
+    #[test]
+    fn meta_cache() {
+        let mut meta_cache = MetaCache::new(2, Kind::FIFO);
+        assert!(meta_cache.set_with_meta("/index", "<html>", "etag-1"));
+        assert!(meta_cache.set_with_meta("/about", "<p>", "etag-2"));
+        assert_eq!(meta_cache.get(&"/index"), Some(&"<html>"));
+        assert_eq!(meta_cache.meta(&"/index"), Some(&"etag-1"));
+
+        assert!(meta_cache.set_with_meta("/index", "<html lang>", "etag-3"));
+        assert_eq!(meta_cache.meta(&"/index"), Some(&"etag-3"));
+
+        assert!(meta_cache.set_with_meta("/contact", "<form>", "etag-4"));
+        assert_eq!(meta_cache.get(&"/index"), None);
+        assert_eq!(meta_cache.meta(&"/index"), None);
+        assert_eq!(meta_cache.meta(&"/about"), Some(&"etag-2"));
+        assert_eq!(meta_cache.len(), 2);
+    }
+
+    #[test]
+    fn queue_clone_from() {
+        let mut source: Queue<i32, String> = Queue::new(3, Kind::LIFO);
+        for key in 1..=4 {
+            assert!(source.set(key, key.to_string()));
+        }
+        assert!(source.pin(&1));
+
+        let mut target: Queue<i32, String> = Queue::new(16, Kind::FIFO);
+        for key in 10..20 {
+            assert!(target.set(key, key.to_string()));
+        }
+        target.clone_from(&source);
+        let fresh = source.clone();
+
+        assert_eq!(target.key_order(), fresh.key_order());
+        assert_eq!(target.capacity(), fresh.capacity());
+        assert_eq!(target.len(), fresh.len());
+        assert!((1..=4).all(|key| target.get(&key) == fresh.get(&key)));
+        assert_eq!(target.get(&10), None);
+
+        assert!(target.set(5, String::from("5")));
+        assert!(source.set(5, String::from("5")));
+        assert_eq!(target.key_order(), source.key_order());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn randomized_invariants() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut fifo_cache: Queue<u8, u32> = Queue::new(8, Kind::FIFO);
+        let mut lifo_cache: Queue<u8, u32> = Queue::new(8, Kind::LIFO);
+        let mut rr_cache: RR<u8, u32> = RR::new(8);
+        for step in 0..5_000 {
+            let key = rng.gen_range(0..24);
+            match rng.gen_range(0..6) {
+                0..=2 => {
+                    fifo_cache.set(key, step);
+                    lifo_cache.set(key, step);
+                    rr_cache.set(key, step);
+                }
+                3 => {
+                    fifo_cache.take(&key);
+                    lifo_cache.remove(&key);
+                }
+                4 => {
+                    fifo_cache.pin(&key);
+                    lifo_cache.unpin(&key);
+                    fifo_cache.evict_back();
+                    lifo_cache.evict_front();
+                }
+                _ => {
+                    fifo_cache.unpin(&key);
+                    rr_cache.compact();
+                }
+            }
+            fifo_cache.check_invariants();
+            lifo_cache.check_invariants();
+            rr_cache.check_invariants();
+        }
+    }
+
+    #[test]
+    fn promotion_cache() {
+        let mut promotion_cache = PromotionCache::new(2, 2, 3);
+        assert!(promotion_cache.set("hot", 0));
+        assert!(promotion_cache.set("warm", 1));
+        for _ in 0..3 {
+            assert_eq!(promotion_cache.get(&"hot"), Some(&0));
+        }
+        assert!(promotion_cache.is_protected(&"hot"));
+        assert_eq!(promotion_cache.get(&"warm"), Some(&1));
+        assert!(!promotion_cache.is_protected(&"warm"));
+
+        for key in ["a", "b", "c", "d"] {
+            assert!(promotion_cache.set(key, 2));
+        }
+        assert_eq!(promotion_cache.get(&"hot"), Some(&0));
+        assert_eq!(promotion_cache.get(&"warm"), None);
+        assert_eq!(promotion_cache.get(&"a"), None);
+        assert_eq!(promotion_cache.len(), 3);
+    }
+
+    #[test]
+    fn promotion_cache_without_protected_segment() {
+        let mut no_protected = PromotionCache::new(4, 0, 1);
+        assert!(no_protected.set(1, "one"));
+        assert_eq!(no_protected.get(&1), Some(&"one"));
+        assert_eq!(no_protected.get(&1), Some(&"one"));
+        assert_eq!(no_protected.len(), 1);
+        assert!(!no_protected.is_protected(&1));
+    }
+
+    #[test]
+    fn promotion_cache_demotes_when_protected_is_full() {
+        let mut demoting = PromotionCache::new(2, 1, 1);
+        assert!(demoting.set("a", 1));
+        assert!(demoting.set("b", 2));
+        assert_eq!(demoting.get(&"a"), Some(&1));
+        assert_eq!(demoting.get(&"b"), Some(&2));
+        assert!(demoting.is_protected(&"b"));
+        assert!(!demoting.is_protected(&"a"));
+        assert_eq!(demoting.len(), 2);
+        assert_eq!(demoting.get(&"a"), Some(&1));
+        assert!(demoting.is_protected(&"a"));
+        assert!(!demoting.is_protected(&"b"));
+        assert_eq!(demoting.len(), 2);
+    }
+
+    #[test]
+    fn tracing_cache() {
+        let mut tracing_cache = TracingCache::new(Queue::new(2, Kind::FIFO));
+        assert!(tracing_cache.set(1, "one"));
+        assert_eq!(tracing_cache.get(&1), Some(&"one"));
+        assert!(tracing_cache.set(2, "two"));
+        assert_eq!(tracing_cache.get(&3), None);
+
+        let trace = tracing_cache.take_trace();
+        assert_eq!(
+            trace,
+            vec![
+                TraceOp::Set(1),
+                TraceOp::Get(1),
+                TraceOp::Set(2),
+                TraceOp::Get(3),
+            ]
+        );
+        assert!(tracing_cache.take_trace().is_empty());
+
+        let mut rr_cache = RR::new(2);
+        assert_eq!(replay(&trace, &mut rr_cache, |key| *key * 10), 1);
+        assert_eq!(rr_cache.get(&2), Some(&20));
+    }
+
+    #[test]
+    fn borrowed_lookups() {
+        let mut fifo_cache: Queue<String, i32> = Queue::new(2, Kind::FIFO);
+        assert!(fifo_cache.set(String::from("one"), 1));
+        assert_eq!(fifo_cache.get("one"), Some(&1));
+        assert_eq!(fifo_cache.get_cloned("one"), Some(1));
+        assert!(fifo_cache.contains_key("one"));
+        assert!(!fifo_cache.contains_key("two"));
+
+        let mut rr_cache: RR<&str, i32> = RR::new(2);
+        assert!(rr_cache.set("one", 1));
+        let owned = String::from("one");
+        assert_eq!(rr_cache.get(owned.as_str()), Some(&1));
+        assert_eq!(rr_cache.get_cloned("one"), Some(1));
+        assert!(rr_cache.contains("one"));
+    }
+
+    #[test]
+    fn loading_cache() {
+        let loads = Rc::new(Cell::new(0));
+        let loader_loads = Rc::clone(&loads);
+        let mut loading_cache = LoadingCache::new(2, Kind::FIFO, move |key: &i32| {
+            loader_loads.set(loader_loads.get() + 1);
+            if *key < 0 {
+                None
+            } else {
+                Some(key * 10)
+            }
+        });
+
+        assert_eq!(loading_cache.get(&1), Some(&10));
+        assert_eq!(loading_cache.get(&1), Some(&10));
+        assert_eq!(loads.get(), 1);
+
+        assert_eq!(loading_cache.get(&2), Some(&20));
+        assert_eq!(loads.get(), 2);
+        assert_eq!(loading_cache.get(&-1), None);
+        assert_eq!(loading_cache.get(&-1), None);
+        assert_eq!(loads.get(), 4);
+        assert_eq!(loading_cache.len(), 2);
+    }
+
+    #[test]
+    fn last_set_evicted() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(1, Kind::FIFO);
+        assert!(!fifo_cache.last_set_evicted());
+        assert!(fifo_cache.set(1, "one"));
+        assert!(!fifo_cache.last_set_evicted());
+        assert!(fifo_cache.set(2, "two"));
+        assert!(fifo_cache.last_set_evicted());
+        assert!(fifo_cache.set(2, "dos"));
+        assert!(!fifo_cache.last_set_evicted());
+
+        let mut rr_cache = RR::new(1);
+        assert!(rr_cache.set(1, "one"));
+        assert!(!rr_cache.last_set_evicted());
+        assert!(rr_cache.set(2, "two"));
+        assert!(rr_cache.last_set_evicted());
+    }
+
+    #[test]
+    fn queue_update_position() {
+        let mut keeps_position: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
+        for (key, value) in [(1, "one"), (2, "two"), (3, "three"), (1, "uno")] {
+            assert!(keeps_position.set(key, value));
+        }
+        assert_eq!(
+            keeps_position.set_reporting(4, "four"),
+            SetOutcome::Evicted(1, "uno")
+        );
+
+        let mut moves_to_back: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
+        moves_to_back.set_update_moves_to_back(true);
+        for (key, value) in [(1, "one"), (2, "two"), (3, "three"), (1, "uno")] {
+            assert!(moves_to_back.set(key, value));
+        }
+        assert_eq!(moves_to_back.key_order(), vec![&2, &3, &1]);
+        assert_eq!(
+            moves_to_back.set_reporting(4, "four"),
+            SetOutcome::Evicted(2, "two")
+        );
+    }
+
+    #[test]
+    fn queue_overwrite_keeps_eviction_position() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(4, Kind::FIFO);
+        for (key, value) in [(1, "one"), (2, "two"), (3, "three"), (4, "four")] {
+            assert!(fifo_cache.set(key, value));
+        }
+        assert!(fifo_cache.set(2, "dos"));
+        assert_eq!(
+            fifo_cache.iter().collect::<Vec<_>>(),
+            vec![(&1, &"one"), (&2, &"dos"), (&3, &"three"), (&4, &"four")]
+        );
+
+        assert_eq!(
+            fifo_cache.set_reporting(5, "five"),
+            SetOutcome::Evicted(1, "one")
+        );
+        assert_eq!(
+            fifo_cache.set_reporting(6, "six"),
+            SetOutcome::Evicted(2, "dos")
+        );
+        assert_eq!(
+            fifo_cache.iter().collect::<Vec<_>>(),
+            vec![(&3, &"three"), (&4, &"four"), (&5, &"five"), (&6, &"six")]
+        );
+    }
+
+    #[test]
+    fn queue_get_or_insert_with_status() {
+        let mut fifo_cache: Queue<&str, u32> = Queue::new(2, Kind::FIFO);
+        let calls = Cell::new(0);
+        let compute = || {
+            calls.set(calls.get() + 1);
+            42
+        };
+        assert_eq!(
+            fifo_cache.get_or_insert_with_status("answer", compute),
+            Some((&42, false))
+        );
+        assert_eq!(
+            fifo_cache.get_or_insert_with_status("answer", compute),
+            Some((&42, true))
+        );
+        assert_eq!(calls.get(), 1);
+        assert_eq!(fifo_cache.get_or_insert_with("other", || 7), Some(&7));
+
+        assert!(fifo_cache.pin(&"answer"));
+        assert!(fifo_cache.pin(&"other"));
+        assert_eq!(fifo_cache.get_or_insert_with_status("third", || 3), None);
+    }
+
+    #[test]
+    fn lru_k_cache() {
+        let mut lru_k_cache: LruKCache<u32, &str> = LruKCache::new(3, 2);
+        assert!(lru_k_cache.set(1, "hot"));
+        assert_eq!(lru_k_cache.get(&1), Some(&"hot"));
+
+        for key in 100..120 {
+            assert!(lru_k_cache.set(key, "scan"));
+            assert!(lru_k_cache.len() <= 3);
+        }
+        assert_eq!(lru_k_cache.get(&1), Some(&"hot"));
+        assert_eq!(lru_k_cache.get(&119), Some(&"scan"));
+        assert_eq!(lru_k_cache.get(&118), Some(&"scan"));
+        assert_eq!(lru_k_cache.get(&117), None);
+        assert_eq!(lru_k_cache.len(), 3);
+
+        // Once every entry has K accesses, the oldest Kth most recent access goes first.
+        assert!(lru_k_cache.set(4, "new"));
+        assert_eq!(lru_k_cache.get(&1), None);
+        assert_eq!(lru_k_cache.get(&118), Some(&"scan"));
+
+        assert!(lru_k_cache.set(4, "newer"));
+        assert_eq!(lru_k_cache.get(&4), Some(&"newer"));
+        assert_eq!(lru_k_cache.len(), 3);
+    }
+
+    #[test]
+    fn set_with_veto() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(3, Kind::FIFO);
+        for (key, value) in [(1, "in flight"), (2, "two"), (3, "three")] {
+            assert!(fifo_cache.set(key, value));
+        }
+        let in_flight = |_: &i32, value: &&str| *value == "in flight";
+        assert!(fifo_cache.set_with_veto(4, "four", in_flight));
+        assert_eq!(fifo_cache.key_order(), vec![&1, &3, &4]);
+        assert!(!fifo_cache.set_with_veto(5, "five", |_, _| true));
+        assert_eq!(fifo_cache.len(), 3);
+
+        for _ in 0..20 {
+            let mut rr_cache = RR::new(2);
+            assert!(rr_cache.set(1, "in flight"));
+            assert!(rr_cache.set(2, "two"));
+            assert!(rr_cache.set_with_veto(3, "three", in_flight));
+            assert!(rr_cache.contains(&1));
+            assert!(!rr_cache.contains(&2));
+            assert!(!rr_cache.set_with_veto(4, "four", |_, _| true));
+            assert_eq!(rr_cache.len(), 2);
+        }
+    }
+
+    #[test]
+    fn random_replacement_remove() {
+        let mut rr_cache = RR::new(3);
+        for key in 1..=3 {
+            assert!(rr_cache.set(key, key));
+        }
+        assert!(rr_cache.pin(&1));
+        assert!(rr_cache.remove(&1));
+        assert!(!rr_cache.remove(&1));
+        assert!(!rr_cache.unpin(&1));
+        assert_eq!(rr_cache.get(&1), None);
+        assert_eq!(rr_cache.len(), 2);
+        assert!(rr_cache.set(4, 4));
+        assert_eq!(rr_cache.len(), 3);
+    }
+
+    #[test]
+    fn bounded_set() {
+        let mut fifo_set = BoundedSet::new(3, Policy::FIFO);
+        for id in 1..=3 {
+            assert!(fifo_set.insert(id));
+        }
+        assert!(fifo_set.insert(2));
+        assert!(fifo_set.insert(4));
+        assert!(!fifo_set.contains(&1));
+        assert!(fifo_set.contains(&2));
+        assert!(fifo_set.remove(&2));
+        assert!(!fifo_set.remove(&2));
+        assert_eq!(fifo_set.len(), 2);
+
+        let mut lifo_set = BoundedSet::new(2, Policy::LIFO);
+        for id in 1..=3 {
+            assert!(lifo_set.insert(id));
+        }
+        assert!(lifo_set.contains(&1));
+        assert!(!lifo_set.contains(&2));
+        assert!(lifo_set.contains(&3));
+
+        let mut rr_set = BoundedSet::new(2, Policy::RR);
+        for id in 1..=10 {
+            assert!(rr_set.insert(id));
+            assert!(rr_set.contains(&id));
+        }
+        assert_eq!(rr_set.len(), 2);
+        assert_eq!(rr_set.capacity(), 2);
+    }
+
+    #[test]
+    fn random_replacement_shared_get() {
+        let mut rr_cache = RR::new(2);
+        assert!(rr_cache.set(1, "one"));
+        assert!(rr_cache.set(2, "two"));
+        let shared = &rr_cache;
+        let (one, two) = (shared.get(&1), shared.get(&2));
+        assert_eq!(one, Some(&"one"));
+        assert_eq!(two, Some(&"two"));
+    }
+
+    #[test]
+    fn queue_recently_evicted() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        assert!(fifo_cache.set(0, 0));
+        assert!(fifo_cache.set(1, 1));
+        assert!(fifo_cache.set(2, 2));
+        assert_eq!(fifo_cache.recently_evicted().count(), 0);
+
+        fifo_cache.set_eviction_history(3);
+        for key in 3..=6 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(
+            fifo_cache.recently_evicted().collect::<Vec<_>>(),
+            vec![&2, &3, &4]
+        );
+        assert_eq!(fifo_cache.take(&6), Some(6));
+        assert_eq!(fifo_cache.evict_front(), Some((5, 5)));
+        assert_eq!(
+            fifo_cache.recently_evicted().collect::<Vec<_>>(),
+            vec![&3, &4, &5]
+        );
+        fifo_cache.set_eviction_history(1);
+        assert_eq!(fifo_cache.recently_evicted().collect::<Vec<_>>(), vec![&5]);
+    }
+
+    #[test]
+    fn capacity_policy() {
+        let mut exact: Queue<i32, i32> =
+            Queue::with_capacity_policy(CapacityPolicy::Exact(2), Kind::FIFO);
+        assert_eq!(exact.capacity(), 2);
+        assert!(exact.set(1, 1));
+        assert!(exact.set(2, 2));
+        assert!(exact.set(3, 3));
+        assert_eq!(exact.len(), 2);
+
+        let mut at_least: Queue<i32, i32> =
+            Queue::with_capacity_policy(CapacityPolicy::AtLeast(2), Kind::FIFO);
+        let bound = at_least.capacity();
+        assert!(bound >= 2);
+        for key in 0..bound as i32 {
+            assert_eq!(at_least.set_reporting(key, key), SetOutcome::Inserted);
+        }
+        assert!(at_least.set_reporting(-1, -1).is_eviction());
+
+        let mut rr_cache = RR::with_capacity_policy(CapacityPolicy::AtLeast(2));
+        let bound = rr_cache.capacity();
+        assert!(bound >= 2);
+        for key in 0..bound {
+            assert_eq!(rr_cache.set_reporting(key, key), SetOutcome::Inserted);
+        }
+        assert_eq!(
+            RR::<i32, i32>::with_capacity_policy(CapacityPolicy::Exact(2)).capacity(),
+            2
+        );
+    }
+
+    #[test]
+    fn frozen_cache() {
+        fn assert_sync<T: Sync>(_: &T) {}
+
+        let mut fifo_cache: Queue<String, i32> = Queue::new(2, Kind::FIFO);
+        assert!(fifo_cache.set(String::from("one"), 1));
+        assert!(fifo_cache.set(String::from("two"), 2));
+        let frozen: FrozenCache<String, i32> = fifo_cache.freeze();
+        assert_sync(&frozen);
+
+        assert!(fifo_cache.set(String::from("three"), 3));
+        assert!(fifo_cache.set(String::from("two"), 22));
+        assert_eq!(frozen.get("one"), Some(&1));
+        assert_eq!(frozen.get("two"), Some(&2));
+        assert!(!frozen.contains_key("three"));
+        assert_eq!(
+            frozen.iter().collect::<Vec<_>>(),
+            vec![(&String::from("one"), &1), (&String::from("two"), &2)]
+        );
+
+        let mut rr_cache = RR::new(2);
+        assert!(rr_cache.set(1, "one"));
+        let frozen = rr_cache.freeze();
+        assert!(rr_cache.remove(&1));
+        assert_eq!(frozen.get(&1), Some(&"one"));
+        assert_eq!(frozen.len(), 1);
+    }
+
+    #[test]
+    fn weighted_cache() {
+        let by_len = |_: &i32, value: &String| value.len();
+
+        let mut entry_bound = WeightedCache::new(2, 100, Kind::FIFO, by_len);
+        assert!(entry_bound.set(1, String::from("a")));
+        assert!(entry_bound.set(2, String::from("b")));
+        assert!(entry_bound.set(3, String::from("c")));
+        assert_eq!(entry_bound.get(&1), None);
+        assert_eq!((entry_bound.len(), entry_bound.weight()), (2, 2));
+
+        let mut weight_bound = WeightedCache::new(10, 6, Kind::FIFO, by_len);
+        assert!(weight_bound.set(1, String::from("aaa")));
+        assert!(weight_bound.set(2, String::from("bb")));
+        assert!(weight_bound.set(3, String::from("cc")));
+        assert_eq!(weight_bound.get(&1), None);
+        assert_eq!((weight_bound.len(), weight_bound.weight()), (2, 4));
+        assert!(!weight_bound.set(4, String::from("too heavy")));
+        assert_eq!(weight_bound.len(), 2);
+        assert!(weight_bound.set(2, String::from("bbbb")));
+        assert_eq!((weight_bound.len(), weight_bound.weight()), (2, 6));
+        assert!(weight_bound.set(2, String::from("bbbbb")));
+        assert_eq!(weight_bound.get(&3), None);
+        assert_eq!((weight_bound.len(), weight_bound.weight()), (1, 5));
+
+        let mut both = WeightedCache::new(3, 10, Kind::FIFO, by_len);
+        for key in 1..=3 {
+            assert!(both.set(key, String::from("x")));
+        }
+        assert!(both.set(4, String::from("123456789")));
+        assert_eq!(both.get(&1), None);
+        assert_eq!(both.get(&2), None);
+        assert_eq!(both.get(&3), Some(&String::from("x")));
+        assert_eq!((both.len(), both.weight()), (2, 10));
+    }
+
+    #[test]
+    fn weighted_cache_expected_entries() {
+        let by_len = |_: &i32, value: &String| value.len();
+        let mut weighted_cache =
+            WeightedCache::with_expected_entries(1_000, 10, 3, Kind::FIFO, by_len);
+        let (map_capacity, keys_capacity) = weighted_cache.storage_capacity();
+        assert!((3..100).contains(&map_capacity));
+        assert!((3..100).contains(&keys_capacity));
+
+        for key in 0..10 {
+            assert!(weighted_cache.set(key, String::from("abc")));
+        }
+        assert_eq!((weighted_cache.len(), weighted_cache.weight()), (3, 9));
+        assert_eq!(weighted_cache.get(&6), None);
+        assert_eq!(weighted_cache.get(&7), Some(&String::from("abc")));
+        assert_eq!(weighted_cache.max_entries(), 1_000);
+    }
+
+    #[test]
+    fn weighted_cache_typed_capacity() {
+        let by_len = |_: &i32, value: &String| value.len();
+        let mut by_entries = WeightedCache::with_capacity(Capacity::Entries(2), Kind::FIFO, by_len);
+        assert!(by_entries.set(1, "a".repeat(1_000)));
+        assert!(by_entries.set(2, "b".repeat(1_000)));
+        assert!(by_entries.set(3, String::from("c")));
+        assert_eq!(by_entries.get(&1), None);
+        assert_eq!((by_entries.len(), by_entries.weight()), (2, 1_001));
+
+        let mut by_bytes = WeightedCache::with_capacity(Capacity::Bytes(6), Kind::FIFO, by_len);
+        for key in 0..6 {
+            assert!(by_bytes.set(key, String::from("x")));
+        }
+        assert_eq!((by_bytes.len(), by_bytes.weight()), (6, 6));
+        assert!(by_bytes.set(6, String::from("yyy")));
+        assert_eq!(by_bytes.get(&2), None);
+        assert_eq!(by_bytes.get(&3), Some(&String::from("x")));
+        assert_eq!((by_bytes.len(), by_bytes.weight()), (4, 6));
+        assert!(!by_bytes.set(7, "z".repeat(7)));
+    }
+
+    #[test]
+    fn weighted_cache_unbounded_weight() {
+        let by_value = |_: &i32, value: &usize| *value;
+        let mut weighted_cache = WeightedCache::new(10, usize::MAX, Kind::FIFO, by_value);
+        assert!(weighted_cache.set(1, usize::MAX - 1));
+        assert!(weighted_cache.set(2, 5));
+        assert_eq!(weighted_cache.get(&1), None);
+        assert_eq!((weighted_cache.len(), weighted_cache.weight()), (1, 5));
+        assert!(weighted_cache.set(3, usize::MAX - 5));
+        assert_eq!(
+            (weighted_cache.len(), weighted_cache.weight()),
+            (2, usize::MAX)
+        );
+    }
+
+    #[test]
+    fn random_replacement_next_eviction_candidate() {
+        let mut rr_cache = RR::with_seed(3, 7);
+        assert_eq!(rr_cache.next_eviction_candidate(), None);
+        for key in 1..=3 {
+            assert!(rr_cache.set(key, key));
+        }
+        for key in 4..50 {
+            let candidate = rr_cache.next_eviction_candidate().copied();
+            let evicted = rr_cache.set_reporting(key, key).into_evicted();
+            assert_eq!(candidate, evicted.map(|(key, _)| key));
+        }
+
+        let evictions = |seed| {
+            let mut rr_cache = RR::with_seed(2, seed);
+            (0..20)
+                .filter_map(|key| rr_cache.set_reporting(key, ()).into_evicted())
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(evictions(42), evictions(42));
+    }
+
+    #[test]
+    fn get_or_default() {
+        let mut fifo_cache: Queue<&str, u32> = Queue::new(2, Kind::FIFO);
+        assert!(fifo_cache.set("hits", 3));
+        assert_eq!(fifo_cache.get_or_default("hits"), 3);
+        assert_eq!(fifo_cache.get_or_default("misses"), 0);
+        assert_eq!(fifo_cache.len(), 1);
+
+        let mut rr_cache: RR<u32, Vec<u32>> = RR::new(2);
+        assert!(rr_cache.set(1, vec![1]));
+        assert_eq!(rr_cache.get_or_default(&1), vec![1]);
+        assert_eq!(rr_cache.get_or_default(&2), Vec::<u32>::new());
+        assert_eq!(rr_cache.len(), 1);
+    }
+
+    #[test]
+    fn weighted_cache_invariants() {
+        let by_len = |_: &usize, value: &String| value.len();
+        // Heavy values hit the weight bound first, light ones the entry bound.
+        for (max_entries, max_weight, value_len) in [(10, 20, 7), (3, 100, 2)] {
+            let mut weighted_cache =
+                WeightedCache::new(max_entries, max_weight, Kind::FIFO, by_len);
+            for key in 0..50 {
+                assert!(weighted_cache.set(key, "x".repeat(value_len)));
+                assert!(weighted_cache.len() <= max_entries);
+                assert!(weighted_cache.weight() <= max_weight);
+                assert_eq!(weighted_cache.weight(), weighted_cache.len() * value_len);
+            }
+            let len = weighted_cache.len();
+            match value_len {
+                7 => assert!(len < max_entries),
+                _ => assert_eq!(len, max_entries),
+            }
+        }
+    }
+
+    #[test]
+    fn queue_capacity_signals() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        fifo_cache.set_signal_sender(Some(sender));
+        assert!(fifo_cache.set(1, 1));
+        assert!(receiver.try_recv().is_err());
+
+        assert!(fifo_cache.set(2, 2));
+        assert_eq!(receiver.try_recv(), Ok(CacheSignal::CapacityReached));
+        assert!(fifo_cache.set(3, 3));
+        assert!(fifo_cache.set(3, 33));
+        assert!(receiver.try_recv().is_err());
+
+        assert_eq!(fifo_cache.take(&2), Some(2));
+        assert_eq!(receiver.try_recv(), Ok(CacheSignal::PressureRelieved));
+        assert_eq!(fifo_cache.take(&3), Some(33));
+        assert!(receiver.try_recv().is_err());
+
+        assert!(fifo_cache.set(4, 4));
+        assert!(fifo_cache.set(5, 5));
+        assert_eq!(receiver.try_recv(), Ok(CacheSignal::CapacityReached));
+        fifo_cache.reserve(1);
+        assert_eq!(receiver.try_recv(), Ok(CacheSignal::PressureRelieved));
+
+        let mut cloned = fifo_cache.clone();
+        assert!(cloned.set(6, 6));
+        assert_eq!(cloned.len(), cloned.capacity());
+
+        let mut target: Queue<i32, i32> = Queue::new(1, Kind::FIFO);
+        target.clone_from(&fifo_cache);
+        assert!(target.set(7, 7));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn queue_clone_from_signals_pressure() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        fifo_cache.set_signal_sender(Some(sender));
+        assert!(fifo_cache.set(1, 1));
+        assert!(fifo_cache.set(2, 2));
+        assert_eq!(receiver.try_recv(), Ok(CacheSignal::CapacityReached));
+
+        let empty: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        fifo_cache.clone_from(&empty);
+        assert_eq!(receiver.try_recv(), Ok(CacheSignal::PressureRelieved));
+
+        let mut full: Queue<i32, i32> = Queue::new(1, Kind::FIFO);
+        assert!(full.set(3, 3));
+        fifo_cache.clone_from(&full);
+        assert_eq!(receiver.try_recv(), Ok(CacheSignal::CapacityReached));
+        fifo_cache.clone_from(&full);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn into_frozen_and_thaw() {
+        let mut fifo_cache: Queue<i32, String> = Queue::new(3, Kind::FIFO);
+        for key in 1..=3 {
+            assert!(fifo_cache.set(key, key.to_string()));
+        }
+        let frozen = fifo_cache.into_frozen();
+        assert_eq!(frozen.get(&2), Some(&String::from("2")));
+        assert_eq!(frozen.peek(&2), frozen.get(&2));
+        assert_eq!(frozen.peek(&4), None);
+        assert_eq!(
+            frozen.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        let mut thawed = frozen.thaw(3, Kind::FIFO);
+        assert_eq!(thawed.key_order(), vec![&1, &2, &3]);
+        assert!(thawed.set(4, String::from("4")));
+        assert_eq!(thawed.get(&1), None);
+
+        let mut rr_cache = RR::new(2);
+        assert!(rr_cache.set(1, "one"));
+        assert!(rr_cache.set(2, "two"));
+        let frozen = rr_cache.into_frozen();
+        assert_eq!(frozen.len(), 2);
+        assert_eq!(frozen.get(&1), Some(&"one"));
+    }
+
+    #[test]
+    fn invalidate_where() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(10, Kind::FIFO);
+        for key in 0..10 {
+            assert!(fifo_cache.set(key, key * 10));
+        }
+        assert!(fifo_cache.pin(&4));
+        assert_eq!(fifo_cache.invalidate_where(|_, value| value % 20 == 0), 5);
+        assert_eq!(fifo_cache.len(), 5);
+        assert_eq!(fifo_cache.key_order(), vec![&1, &3, &5, &7, &9]);
+        assert!(!fifo_cache.unpin(&4));
+        assert_eq!(fifo_cache.invalidate_where(|_, _| false), 0);
+
+        let mut rr_cache = RR::new(10);
+        for key in 0..10 {
+            assert!(rr_cache.set(key, key * 10));
+        }
+        assert!(rr_cache.pin(&3));
+        assert_eq!(rr_cache.invalidate_where(|key, _| *key < 4), 4);
+        assert_eq!(rr_cache.len(), 6);
+        assert_eq!(rr_cache.get(&3), None);
+        assert_eq!(rr_cache.get(&4), Some(&40));
+        for key in 10..20 {
+            assert!(rr_cache.set(key, key));
+        }
+        assert_eq!(rr_cache.len(), 10);
+    }
+
+    #[test]
+    fn queue_set_kind() {
+        let mut queue: Queue<i32, i32> = Queue::new(3, Kind::FIFO);
+        for key in 1..=4 {
+            assert!(queue.set(key, key));
+        }
+        assert_eq!(queue.key_order(), vec![&2, &3, &4]);
+
+        queue.set_kind(Kind::LIFO);
+        assert_eq!(queue.kind(), Kind::LIFO);
+        assert_eq!(queue.key_order(), vec![&2, &3, &4]);
+        assert_eq!(queue.set_reporting(5, 5), SetOutcome::Evicted(4, 4));
+        assert_eq!(queue.set_reporting(6, 6), SetOutcome::Evicted(5, 5));
+        assert_eq!(queue.key_order(), vec![&2, &3, &6]);
+    }
+
+    #[test]
+    fn on_memory_pressure() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(10, Kind::FIFO);
+        fifo_cache.set_eviction_history(10);
+        for key in 0..10 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(fifo_cache.on_memory_pressure(0.5), 5);
+        assert_eq!(fifo_cache.key_order(), vec![&5, &6, &7, &8, &9]);
+        assert_eq!(
+            fifo_cache.recently_evicted().collect::<Vec<_>>(),
+            vec![&0, &1, &2, &3, &4]
+        );
+        assert_eq!(fifo_cache.on_memory_pressure(0.0), 0);
+        assert_eq!(fifo_cache.on_memory_pressure(0.1), 1);
+
+        let mut lifo_cache: Queue<i32, i32> = Queue::new(4, Kind::LIFO);
+        for key in 0..4 {
+            assert!(lifo_cache.set(key, key));
+        }
+        assert!(lifo_cache.pin(&3));
+        assert_eq!(lifo_cache.on_memory_pressure(2.0), 3);
+        assert_eq!(lifo_cache.key_order(), vec![&3]);
+
+        let mut rr_cache = RR::new(10);
+        for key in 0..10 {
+            assert!(rr_cache.set(key, key));
+        }
+        assert!(rr_cache.pin(&0));
+        assert_eq!(rr_cache.on_memory_pressure(0.5), 5);
+        assert_eq!(rr_cache.len(), 5);
+        assert!(rr_cache.contains(&0));
+    }
+
+    #[test]
+    fn utilization() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(4, Kind::FIFO);
+        assert_eq!(fifo_cache.utilization(), 0.0);
+        assert!(fifo_cache.set(1, 1));
+        assert_eq!(fifo_cache.utilization(), 0.25);
+        for key in 2..10 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(fifo_cache.utilization(), 1.0);
+
+        let mut rr_cache = RR::new(2);
+        assert_eq!(rr_cache.utilization(), 0.0);
+        assert!(rr_cache.set(1, 1));
+        assert!(rr_cache.set(2, 2));
+        assert_eq!(rr_cache.utilization(), 1.0);
+        assert_eq!(RR::<i32, i32>::new(0).utilization(), 0.0);
+    }
+
+    #[test]
+    fn random_replacement_weighted() {
+        let (mut heavy_survivors, mut light_survivors) = (0, 0);
+        for seed in 0..100 {
+            let mut rr_cache = RR::with_seed(10, seed);
+            for key in 0..5 {
+                assert!(rr_cache.set_weighted(key, (), 50));
+                assert!(rr_cache.set_weighted(key + 5, (), 1));
+            }
+            for key in 10..15 {
+                assert!(rr_cache.set(key, ()));
+            }
+            assert_eq!(rr_cache.len(), 10);
+            heavy_survivors += (0..5).filter(|key| rr_cache.contains(key)).count();
+            light_survivors += (5..10).filter(|key| rr_cache.contains(key)).count();
+        }
+        assert!(heavy_survivors > 480, "{}", heavy_survivors);
+        assert!(light_survivors < 200, "{}", light_survivors);
+    }
+
+    #[test]
+    fn scored_cache() {
+        let mut scored_cache = ScoredCache::new(3, |score: &u32| *score);
+        assert!(scored_cache.set("a", 30));
+        assert!(scored_cache.set("b", 10));
+        assert!(scored_cache.set("c", 20));
+        assert!(scored_cache.set("d", 40));
+        assert_eq!(scored_cache.get(&"b"), None);
+
+        assert!(scored_cache.update(&"d", |score| *score = 5));
+        assert!(!scored_cache.update(&"b", |score| *score = 50));
+        assert!(scored_cache.set("e", 25));
+        assert_eq!(scored_cache.get(&"d"), None);
+        assert_eq!(scored_cache.len(), 3);
+
+        assert!(scored_cache.set("c", 100));
+        assert!(scored_cache.set("f", 50));
+        assert_eq!(scored_cache.get(&"e"), None);
+        assert_eq!(scored_cache.get(&"c"), Some(&100));
+
+        let mut priority_cache = PriorityCache::new(2);
+        assert!(priority_cache.set("x", (), 1));
+        assert!(priority_cache.set("y", (), 2));
+        assert!(priority_cache.set_priority(&"x", 3));
+        assert!(priority_cache.set("z", (), 4));
+        assert_eq!(priority_cache.get(&"y"), None);
+    }
+
+    #[test]
+    fn runtime_policy_cache() {
+        for policy in [Policy::FIFO, Policy::LIFO, Policy::RR] {
+            let mut cache = Cache::new(policy, 2);
+            assert_eq!(cache.policy(), policy);
+            assert_eq!(cache.capacity(), 2);
+            assert!(cache.is_empty());
+            assert!(cache.set(1, "one"));
+            assert!(cache.set(2, "two"));
+            assert!(cache.set(3, "three"));
+            assert_eq!(cache.len(), 2);
+            assert_eq!(cache.get(&3), Some(&"three"));
+            let survivor = match policy {
+                Policy::FIFO => 2,
+                Policy::LIFO => 1,
+                Policy::RR => *[1, 2].iter().find(|key| cache.get(key).is_some()).unwrap(),
+            };
+            assert_eq!(cache.get(&(3 - survivor)), None);
+            assert!(cache.remove(&survivor));
+            assert!(!cache.remove(&survivor));
+            assert_eq!(cache.len(), 1);
+        }
+    }
+
+    #[test]
+    fn set_checked() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(10, Kind::FIFO);
+        for key in 1..=3 {
+            assert!(fifo_cache.set_checked(key, key, 3));
+        }
+        assert!(!fifo_cache.set_checked(4, 4, 3));
+        assert!(fifo_cache.set_checked(2, 22, 3));
+        assert_eq!(fifo_cache.get(&2), Some(&22));
+        assert_eq!(fifo_cache.len(), 3);
+        assert!(fifo_cache.set_checked(4, 4, 4));
+
+        let mut full_cache: Queue<i32, i32> = Queue::new(3, Kind::FIFO);
+        for key in 1..=4 {
+            assert!(full_cache.set(key, key));
+        }
+        assert!(full_cache.last_set_evicted());
+        assert!(!full_cache.set_checked(5, 5, 3));
+        assert!(!full_cache.last_set_evicted());
+
+        let mut rr_cache = RR::new(10);
+        for key in 1..=3 {
+            assert!(rr_cache.set_checked(key, key, 3));
+        }
+        assert!(!rr_cache.set_checked(4, 4, 3));
+        assert!(rr_cache.set_checked(3, 33, 3));
+        assert_eq!(rr_cache.len(), 3);
+
+        let mut full_rr = RR::new(3);
+        for key in 1..=4 {
+            assert!(full_rr.set(key, key));
+        }
+        assert!(full_rr.last_set_evicted());
+        assert!(!full_rr.set_checked(5, 5, 3));
+        assert!(!full_rr.last_set_evicted());
+    }
+
+    #[test]
+    fn weighted_cache_sampling() {
+        let weighs = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&weighs);
+        let by_len = move |_: &usize, value: &String| {
+            counter.set(counter.get() + 1);
+            value.len()
+        };
+        let mut weighted_cache = WeightedCache::with_sampling(100, 10_000, Kind::FIFO, 4, by_len);
+        for key in 0..200 {
+            assert!(weighted_cache.set(key, "x".repeat(10 + key % 11)));
+        }
+        assert_eq!(weighs.get(), 50);
+        assert_eq!(weighted_cache.len(), 100);
+
+        let exact = weighted_cache.exact_weight();
+        let estimate = weighted_cache.weight();
+        assert!(
+            exact.abs_diff(estimate) * 10 <= exact,
+            "{} vs {}",
+            estimate,
+            exact
+        );
+
+        let mut exact_cache =
+            WeightedCache::new(100, 10_000, Kind::FIFO, |_: &usize, value: &String| {
+                value.len()
+            });
+        for key in 0..200 {
+            assert!(exact_cache.set(key, "x".repeat(10 + key % 11)));
+        }
+        assert_eq!(exact_cache.weight(), exact_cache.exact_weight());
+    }
+
+    #[test]
+    fn queue_reload() {
+        let mut fifo_cache: Queue<&str, i32> = Queue::new(3, Kind::FIFO);
+        for (key, value) in [("hot", 1), ("stale", 2), ("warm", 3)] {
+            assert!(fifo_cache.set(key, value));
+        }
+        let events = fifo_cache.subscribe();
+        fifo_cache.reload([("warm", 30), ("new", 40), ("hot", 10)]);
+        assert_eq!(
+            fifo_cache.iter().collect::<Vec<_>>(),
+            vec![(&"hot", &10), (&"warm", &30), (&"new", &40)]
+        );
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![
+                CacheEvent::Insert("warm"),
+                CacheEvent::Insert("new"),
+                CacheEvent::Insert("hot"),
+            ]
+        );
+        assert_eq!(
+            fifo_cache.set_reporting("next", 50),
+            SetOutcome::Evicted("hot", 10)
+        );
+    }
+
+    #[test]
+    fn queue_contains_many() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(3, Kind::FIFO);
+        for key in 1..=4 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(
+            fifo_cache.contains_many(&[1, 2, 5, 4, 2]),
+            vec![false, true, false, true, true]
+        );
+        assert!(fifo_cache.contains_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn random_replacement_sampled_evicts_within_capacity() {
+        let mut rr_cache = SampledRR::with_seed(3, 11);
+        for key in 0..100 {
+            assert!(rr_cache.set(key, key * 10));
+            assert!(rr_cache.len() <= 3);
+            assert_eq!(rr_cache.get(&key), Some(&(key * 10)));
+        }
+        assert_eq!(rr_cache.len(), 3);
+        assert_eq!((0..100).filter(|key| rr_cache.contains(key)).count(), 3);
+
+        assert!(rr_cache.set(99, 0));
+        assert_eq!(rr_cache.get(&99), Some(&0));
+        assert_eq!(rr_cache.len(), 3);
+
+        let mut empty: SampledRR<i32, i32> = SampledRR::new(0);
+        assert!(!empty.set(1, 1));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn random_replacement_reports_reindexed_keys() {
+        let moves = Arc::new(Mutex::new(Vec::new()));
+        let mut rr_cache = RR::with_seed(4, 3);
+        let recorded = Arc::clone(&moves);
+        rr_cache.set_on_reindex(move |key: &i32, old_idx, new_idx| {
+            recorded.lock().unwrap().push((*key, old_idx, new_idx));
+        });
+        for key in 0..4 {
+            assert!(rr_cache.set(key, key));
+        }
+        assert!(moves.lock().unwrap().is_empty());
+
+        assert_eq!(rr_cache.take(&1), Some(1));
+        assert_eq!(*moves.lock().unwrap(), vec![(3, 3, 1)]);
+
+        assert_eq!(rr_cache.take(&2), Some(2));
+        assert_eq!(moves.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn caches_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RR<u32, u32>>();
+        assert_send::<Cache<u32, u32>>();
+        assert_send::<BoundedSet<u32>>();
+        assert_send::<Queue<u32, u32>>();
+    }
+
+    #[test]
+    fn random_replacement_contains_many() {
+        let mut rr_cache = RR::with_seed(3, 7);
+        for key in 1..=3 {
+            assert!(rr_cache.set(key, key));
+        }
+        assert_eq!(
+            rr_cache.contains_many(&[3, 9, 1, 2, 0]),
+            vec![true, false, true, true, false]
+        );
+        assert_eq!(rr_cache.len(), 3);
+    }
+
+    #[test]
+    fn queue_event_subscribers() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        let first = fifo_cache.subscribe();
+        let second = fifo_cache.subscribe();
+        let dropped = fifo_cache.subscribe();
+        drop(dropped);
+
+        assert!(fifo_cache.set(1, 10));
+        assert!(fifo_cache.set(2, 20));
+        assert_eq!(fifo_cache.get(&1), Some(&10));
+        assert_eq!(fifo_cache.get(&9), None);
+        assert!(fifo_cache.set(3, 30));
+        assert!(fifo_cache.set(3, 31));
+
+        let expected = vec![
+            CacheEvent::Insert(1),
+            CacheEvent::Insert(2),
+            CacheEvent::Hit(1),
+            CacheEvent::Miss,
+            CacheEvent::Evict(1),
+            CacheEvent::Insert(3),
+            CacheEvent::Insert(3),
+        ];
+        assert_eq!(first.try_iter().collect::<Vec<_>>(), expected);
+        assert_eq!(second.try_iter().collect::<Vec<_>>(), expected);
+
+        assert_eq!(fifo_cache.get_or_insert_with(3, || 0), Some(&31));
+        assert_eq!(fifo_cache.get_or_insert_with(4, || 40), Some(&40));
+        assert_eq!(
+            first.try_iter().collect::<Vec<_>>(),
+            vec![
+                CacheEvent::Hit(3),
+                CacheEvent::Miss,
+                CacheEvent::Evict(2),
+                CacheEvent::Insert(4),
+            ]
+        );
+
+        let mut cloned = fifo_cache.clone();
+        assert!(cloned.set(7, 70));
+        assert_eq!(cloned.get(&7), Some(&70));
+
+        let mut target: Queue<i32, i32> = Queue::new(2, Kind::FIFO);
+        let target_events = target.subscribe();
+        target.clone_from(&fifo_cache);
+        assert!(target.set(8, 80));
+        assert_eq!(first.try_recv(), Err(std::sync::mpsc::TryRecvError::Empty));
+        assert_eq!(
+            target_events.try_iter().collect::<Vec<_>>(),
+            vec![CacheEvent::Evict(3), CacheEvent::Insert(8)]
+        );
+    }
+
+    #[test]
+    fn queue_for_each_until() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(5, Kind::FIFO);
+        for key in 1..=5 {
+            assert!(fifo_cache.set(key, key * 10));
+        }
+        let mut visited = Vec::new();
+        fifo_cache.for_each_until(|key, value| {
+            visited.push(*key);
+            if *value >= 30 {
+                return ControlFlow::Break(());
+            }
+            *value += 1;
+            ControlFlow::Continue(())
+        });
+        assert_eq!(visited, vec![1, 2, 3]);
+        let values: Vec<i32> = fifo_cache.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, vec![11, 21, 30, 40, 50]);
+
+        fifo_cache.for_each_until(|_, value| {
+            *value = 0;
+            ControlFlow::Continue(())
+        });
+        assert!(fifo_cache.iter().all(|(_, value)| *value == 0));
+    }
+
+    #[test]
+    fn queue_recent_and_oldest() {
+        let mut fifo_cache: Queue<i32, &str> = Queue::new(4, Kind::FIFO);
+        for (key, value) in [
+            (1, "one"),
+            (2, "two"),
+            (3, "three"),
+            (4, "four"),
+            (5, "five"),
+        ] {
+            assert!(fifo_cache.set(key, value));
+        }
+        assert_eq!(fifo_cache.recent(2), vec![(&5, &"five"), (&4, &"four")]);
+        assert_eq!(fifo_cache.oldest(2), vec![(&2, &"two"), (&3, &"three")]);
+        assert_eq!(fifo_cache.oldest(10).len(), 4);
+        assert!(fifo_cache.recent(0).is_empty());
+        assert_eq!(fifo_cache.key_order(), vec![&2, &3, &4, &5]);
+    }
+
+    #[test]
+    fn queue_pause_eviction() {
+        let mut fifo_cache: Queue<i32, i32> = Queue::new(3, Kind::FIFO);
+        fifo_cache.pause_eviction();
+        for key in 1..=5 {
+            assert_eq!(fifo_cache.set_reporting(key, key), SetOutcome::Inserted);
+        }
+        assert_eq!(fifo_cache.len(), 5);
+
+        assert_eq!(fifo_cache.resume_eviction(), 2);
+        assert_eq!(fifo_cache.key_order(), vec![&3, &4, &5]);
+        assert_eq!(fifo_cache.set_reporting(6, 6), SetOutcome::Evicted(3, 3));
+        assert_eq!(fifo_cache.resume_eviction(), 0);
+    }
+
+    #[test]
+    fn queue_eviction_order() {
+        for kind in [Kind::FIFO, Kind::LIFO] {
+            let mut queue: Queue<i32, i32> = Queue::new(4, kind);
+            for key in 1..=4 {
+                assert!(queue.set(key, key));
+            }
+            assert!(queue.pin(&2));
+            let order: Vec<i32> = queue.eviction_order().into_iter().copied().collect();
+            match kind {
+                Kind::FIFO => assert_eq!(order, vec![1, 3, 4]),
+                Kind::LIFO => assert_eq!(order, vec![4, 3, 1]),
+            }
+            queue.set_eviction_history(4);
+            assert_eq!(queue.on_memory_pressure(1.0), 3);
+            assert_eq!(queue.recently_evicted().copied().collect::<Vec<_>>(), order);
+        }
+    }
+
+    #[test]
+    fn versioned_cache() {
+        let mut versioned_cache = VersionedCache::new(2, Kind::FIFO);
+        assert!(versioned_cache.set("config", "v1"));
+        let (value, version) = versioned_cache.get_versioned(&"config").unwrap();
+        assert_eq!(value, &"v1");
+        assert!(versioned_cache.is_current(&"config", version));
+
+        assert!(versioned_cache.set("config", "v2"));
+        let (value, updated) = versioned_cache.get_versioned(&"config").unwrap();
+        assert_eq!(value, &"v2");
+        assert!(updated > version);
+        assert!(!versioned_cache.is_current(&"config", version));
+        assert!(versioned_cache.is_current(&"config", updated));
+
+        assert!(versioned_cache.set("a", "a"));
+        assert!(versioned_cache.set("b", "b"));
+        assert!(!versioned_cache.is_current(&"config", updated));
+        assert_eq!(versioned_cache.get_versioned(&"config"), None);
+    }
+
+    #[test]
+    fn partitioned_cache() {
+        let by_namespace = |key: &String| match key.split_once(':') {
+            Some(("noisy", _)) => 1,
+            _ => 0,
+        };
+        let mut partitioned_cache = PartitionedCache::new([2, 3], Kind::FIFO, by_namespace);
+        assert_eq!(partitioned_cache.partitions(), 2);
+        assert!(partitioned_cache.set(String::from("quiet:a"), 1));
+        assert!(partitioned_cache.set(String::from("quiet:b"), 2));
+        for id in 0..50 {
+            assert!(partitioned_cache.set(format!("noisy:{}", id), id));
+        }
+        assert_eq!(partitioned_cache.get(&String::from("quiet:a")), Some(&1));
+        assert_eq!(partitioned_cache.get(&String::from("quiet:b")), Some(&2));
+        assert_eq!(partitioned_cache.partition_len(0), Some(2));
+        assert_eq!(partitioned_cache.partition_len(1), Some(3));
+        assert_eq!(partitioned_cache.partition_len(2), None);
+        assert_eq!(partitioned_cache.len(), 5);
+
+        assert!(partitioned_cache.remove(&String::from("noisy:49")));
+        assert_eq!(partitioned_cache.partition_len(1), Some(2));
+    }
+
+    #[test]
+    fn random_replacement_take() {
+        let mut rr_cache = RR::with_seed(3, 1);
+        for key in 1..=3 {
+            assert!(rr_cache.set(key, key.to_string()));
+        }
+        assert_eq!(rr_cache.take(&1), Some(String::from("1")));
+        assert_eq!(rr_cache.take(&1), None);
+        assert!(!rr_cache.contains(&1));
+        assert_eq!(rr_cache.len(), 2);
+
+        assert_eq!(
+            rr_cache.set_reporting(4, String::from("4")),
+            SetOutcome::Inserted
+        );
+        for key in 5..20 {
+            let candidate = rr_cache.next_eviction_candidate().copied();
+            let evicted = rr_cache.set_reporting(key, key.to_string()).into_evicted();
+            assert_eq!(evicted.map(|(key, _)| key), candidate);
+            assert_eq!(rr_cache.len(), 3);
+        }
+    }
+
+    #[test]
+    fn interned_cache() {
+        let mut interned_cache = InternedCache::new(100, Kind::FIFO);
+        for key in 0..100 {
+            let status = ["active", "idle", "banned"][key % 3];
+            assert!(interned_cache.set(key, String::from(status)));
+        }
+        assert_eq!(interned_cache.len(), 100);
+        assert_eq!(interned_cache.unique_value_count(), 3);
+        assert_eq!(interned_cache.get(&4), Some(&String::from("idle")));
+
+        for key in (2..100).step_by(3) {
+            assert!(interned_cache.set(key, String::from("active")));
+        }
+        assert_eq!(interned_cache.unique_value_count(), 2);
+
+        let mut small = InternedCache::new(1, Kind::FIFO);
+        assert!(small.set(1, 1));
+        assert!(small.set(2, 2));
+        assert_eq!(small.unique_value_count(), 1);
+        assert_eq!(small.get(&2), Some(&2));
+    }
+
+    #[test]
+    fn set_many_evicting_large_batch() {
+        let batch: Vec<(u32, u32)> = (0..1_000).map(|key| (key, key * 2)).collect();
+        // Storage is compared over a batch that fills the cache without evicting,
+        // since removals leave tombstones that lower the map's reported capacity.
+        let mut batched: Queue<u32, u32> = Queue::new(256, Kind::FIFO);
+        let storage = batched.storage_capacity();
+        assert!(batched
+            .set_many_evicting(batch[..256].iter().copied())
+            .is_empty());
+        assert_eq!(batched.storage_capacity(), storage);
+        let evicted = batched.set_many_evicting(batch[256..].iter().copied());
+        let mut one_by_one: Queue<u32, u32> = Queue::new(256, Kind::FIFO);
+        for (key, value) in batch.iter().copied() {
+            assert!(one_by_one.set(key, value));
+        }
+        assert_eq!(evicted.len(), 1_000 - 256);
+        assert_eq!(evicted.first(), Some(&(0, 0)));
+        assert_eq!(batched.key_order(), one_by_one.key_order());
+        assert_eq!(batched.capacity(), 256);
+
+        let mut rr_cache = RR::new(256);
+        let storage = rr_cache.storage_capacity();
+        assert!(rr_cache
+            .set_many_evicting(batch[..256].iter().copied())
+            .is_empty());
+        assert_eq!(rr_cache.storage_capacity(), storage);
+        let evicted = rr_cache.set_many_evicting(batch[256..].iter().copied());
+        assert_eq!(evicted.len(), 1_000 - 256);
+        assert_eq!(rr_cache.len(), 256);
+    }
+
+    #[test]
+    fn gdsf_cache_keeps_costly_small_entries() {
+        let mut cache = GdsfCache::new(3);
+        assert!(cache.set(0, 1, 50.0, 1));
+        assert!(cache.set(1, 2, 20.0, 4));
+        for key in 0..20 {
+            assert!(cache.set(100 + key, 0, 1.0, 100));
+            assert_eq!(cache.len(), 3);
+        }
+        assert!(cache.contains_key(&0));
+        assert!(cache.contains_key(&1));
+        assert!(cache.contains_key(&119));
+        assert!(!cache.contains_key(&118));
+        assert!(cache.inflation() > 0.0);
+
+        assert_eq!(cache.get(&0), Some(&1));
+        let inflation = cache.inflation();
+        assert_eq!(cache.priority(&0), Some(2.0 * 50.0 + inflation));
+
+        let mut empty: GdsfCache<i32, i32> = GdsfCache::new(0);
+        assert!(!empty.set(1, 1, 1.0, 1));
+    }
+
+    #[test]
+    fn nonzero_capacity_constructors() {
+        let capacity = NonZeroUsize::new(2).unwrap();
+        let mut fifo_cache: Queue<i32, i32> = Queue::from_nonzero(capacity, Kind::FIFO);
+        assert_eq!(fifo_cache.capacity(), 2);
+        for key in 1..=3 {
+            assert!(fifo_cache.set(key, key));
+        }
+        assert_eq!(fifo_cache.key_order(), vec![&2, &3]);
+
+        let mut rr_cache = RR::from_nonzero(capacity);
+        assert_eq!(rr_cache.capacity(), 2);
+        assert!(rr_cache.set(1, 1));
+        assert_eq!(rr_cache.get(&1), Some(&1));
+
+        assert_eq!(NonZeroUsize::new(0), None);
+        let mut empty_queue: Queue<i32, i32> = Queue::new(0, Kind::FIFO);
+        assert!(!empty_queue.set(1, 1));
+        let mut empty_rr = RR::new(0);
+        assert!(!empty_rr.set(1, 1));
+    }
+
+    #[test]
+    fn fixed_cache_random_eviction() {
+        let mut fixed_cache: FixedCache<u32, u32, 4> = FixedCache::with_eviction(Eviction::RR);
+        for key in 0..100 {
+            assert!(fixed_cache.set(key, key));
+            assert!(fixed_cache.len() <= 4);
+            assert_eq!(fixed_cache.get(&key), Some(&key));
+        }
+        assert_eq!(fixed_cache.len(), 4);
+        assert_eq!(
+            (0..100)
+                .filter(|key| fixed_cache.get(key).is_some())
+                .count(),
+            4
+        );
+
+        let mut seeded: FixedCache<u32, u32, 4> = FixedCache::with_eviction_seed(Eviction::RR, 42);
+        let mut reseeded: FixedCache<u32, u32, 4> =
+            FixedCache::with_eviction_seed(Eviction::RR, 42);
+        for key in 0..20 {
+            assert!(seeded.set(key, key));
+            assert!(reseeded.set(key, key));
+        }
+        assert_eq!(seeded.len(), 4);
+        let survivors: Vec<u32> = (0..20).filter(|key| seeded.get(key).is_some()).collect();
+        assert_eq!(survivors.len(), 4);
+        assert_eq!(
+            survivors,
+            (0..20)
+                .filter(|key| reseeded.get(key).is_some())
+                .collect::<Vec<_>>()
+        );
     }
 }
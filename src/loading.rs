@@ -0,0 +1,48 @@
+use crate::queue::{Kind, Queue};
+use std::{cmp::Eq, hash::Hash};
+
+type Loader<K, V> = Box<dyn Fn(&K) -> Option<V>>;
+
+/// The loading cache is a read-through `Queue`: a miss calls the loader and caches what it returns.
+/// A loader returning `None` caches nothing, so the next `get` for that key calls it again.
+/// It is single-threaded, so concurrent duplicate loads can't happen.
+pub struct LoadingCache<K, V> {
+    entries: Queue<K, V>,
+    loader: Loader<K, V>,
+}
+
+impl<K, V> LoadingCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(capacity: usize, kind: Kind, loader: impl Fn(&K) -> Option<V> + 'static) -> Self {
+        Self {
+            entries: Queue::new(capacity, kind),
+            loader: Box::new(loader),
+        }
+    }
+
+    // Time: O(1) plus the loader on a miss | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self.entries.contains_key(key) {
+            let value = (self.loader)(key)?;
+            self.entries.set(key.clone(), value);
+        }
+        self.entries.get(key)
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        self.entries.set(key, value)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    cmp::Eq,
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    hash::Hash,
+};
+
+struct Slot<V> {
+    value: V,
+    // The last `k` access times, oldest first.
+    history: VecDeque<u64>,
+}
+
+/// The LRU-K cache evicts the entry whose Kth most recent access is the oldest.
+/// Entries accessed fewer than K times count as infinitely old, and among those the least recently used goes first,
+/// so a burst of keys that are only touched once cannot push out an entry with an established access pattern.
+/// Inserts and updates count as accesses. Time is a logical counter, not the wall clock.
+pub struct LruKCache<K, V> {
+    entry_map: HashMap<K, Slot<V>>,
+    capacity: usize,
+    k: usize,
+    tick: u64,
+}
+
+impl<K, V> LruKCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Panics if `k` is zero.
+    pub fn new(capacity: usize, k: usize) -> Self {
+        assert!(k > 0, "LRU-K needs k of at least 1");
+        Self {
+            entry_map: HashMap::with_capacity(capacity),
+            capacity,
+            k,
+            tick: 0,
+        }
+    }
+
+    // Time: O(1), O(n) when it evicts | Space: O(n * k)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        if !self.entry_map.contains_key(&key) && self.entry_map.len() >= self.capacity {
+            match self.victim() {
+                Some(victim) => self.entry_map.remove(&victim),
+                None => return false,
+            };
+        }
+        let now = self.next_tick();
+        let k = self.k;
+        let slot = match self.entry_map.entry(key) {
+            Entry::Occupied(occupied) => {
+                let slot = occupied.into_mut();
+                slot.value = value;
+                slot
+            }
+            Entry::Vacant(vacant) => vacant.insert(Slot {
+                value,
+                history: VecDeque::with_capacity(k),
+            }),
+        };
+        Self::record(slot, now, k);
+        true
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let now = self.next_tick();
+        let k = self.k;
+        let slot = self.entry_map.get_mut(key)?;
+        Self::record(slot, now, k);
+        Some(&slot.value)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn record(slot: &mut Slot<V>, now: u64, k: usize) {
+        if slot.history.len() == k {
+            slot.history.pop_front();
+        }
+        slot.history.push_back(now);
+    }
+
+    // Entries with fewer than `k` accesses sort first (`None`), then by Kth most recent access,
+    // with the most recent access breaking ties.
+    // Time: O(n) | Space: O(1)
+    fn victim(&self) -> Option<K> {
+        self.entry_map
+            .iter()
+            .min_by_key(|(_, slot)| {
+                let kth = if slot.history.len() == self.k {
+                    slot.history.front().copied()
+                } else {
+                    None
+                };
+                (kth, slot.history.back().copied())
+            })
+            .map(|(key, _)| key.clone())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::queue::{Kind, Queue};
+use std::{cmp::Eq, hash::Hash};
+
+/// The meta cache is a `Queue` whose entries carry metadata, such as an etag, source or version, next to the value.
+/// The metadata has no effect on eviction, so values don't need to grow to hold it.
+pub struct MetaCache<K, V, M> {
+    entries: Queue<K, (V, M)>,
+}
+
+impl<K, V, M> MetaCache<K, V, M>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(capacity: usize, kind: Kind) -> Self {
+        Self {
+            entries: Queue::new(capacity, kind),
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set_with_meta(&mut self, key: K, value: V, meta: M) -> bool {
+        self.entries.set(key, (value, meta))
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn meta(&self, key: &K) -> Option<&M> {
+        self.entries.get(key).map(|(_, meta)| meta)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{
+    clock::{Clock, SystemClock},
+    queue::{Kind, Queue},
+};
+use std::{
+    cmp::Eq,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// The result of a `NegativeCache` lookup.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Cached<V> {
+    /// The key is cached with a value.
+    Hit(V),
+    /// The key is known not to exist in the backing store.
+    Absent,
+    /// Nothing is known about the key.
+    Unknown,
+}
+
+enum Slot<V> {
+    Value(V),
+    // `None` when the expiry overflows `Instant`.
+    Absent(Option<Instant>),
+}
+
+/// The negative cache remembers misses as well as values,
+/// so repeated lookups of keys that don't exist don't reach the backing store.
+/// Absent markers expire after their own TTL, never if it is too large to represent as an `Instant`, and count toward capacity like values do.
+/// Entries are evicted in FIFO order.
+pub struct NegativeCache<K, V, C = SystemClock> {
+    entries: Queue<K, Slot<V>>,
+    absent_ttl: Duration,
+    clock: C,
+}
+
+impl<K, V> NegativeCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(capacity: usize, absent_ttl: Duration) -> Self {
+        Self::with_clock(capacity, absent_ttl, SystemClock)
+    }
+}
+
+impl<K, V, C> NegativeCache<K, V, C>
+where
+    K: Eq + Hash + Clone,
+    C: Clock,
+{
+    pub fn with_clock(capacity: usize, absent_ttl: Duration, clock: C) -> Self {
+        Self {
+            entries: Queue::new(capacity, Kind::FIFO),
+            absent_ttl,
+            clock,
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        self.entries.set(key, Slot::Value(value))
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set_absent(&mut self, key: K) -> bool {
+        let expires_at = self.clock.now().checked_add(self.absent_ttl);
+        self.entries.set(key, Slot::Absent(expires_at))
+    }
+
+    // Time: O(1), O(n) when an expired marker is dropped | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Cached<&V> {
+        let expired = match self.entries.get(key) {
+            Some(Slot::Absent(Some(expires_at))) => self.clock.now() >= *expires_at,
+            Some(Slot::Absent(None)) => false,
+            Some(Slot::Value(_)) => false,
+            None => return Cached::Unknown,
+        };
+        if expired {
+            self.entries.remove(key);
+            return Cached::Unknown;
+        }
+        match self.entries.get(key) {
+            Some(Slot::Value(value)) => Cached::Hit(value),
+            Some(Slot::Absent(_)) => Cached::Absent,
+            None => Cached::Unknown,
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::queue::{Kind, Queue};
+use std::{cmp::Eq, hash::Hash};
+
+type Partitioner<K> = Box<dyn Fn(&K) -> usize>;
+
+/// The partitioned cache routes every key to one of several independent `Queue`s, each with its own capacity,
+/// so a noisy partition can only evict its own entries. The partition function's result is taken modulo
+/// the number of partitions.
+pub struct PartitionedCache<K, V> {
+    partitions: Vec<Queue<K, V>>,
+    partition_of: Partitioner<K>,
+}
+
+impl<K, V> PartitionedCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Creates one partition per capacity. Panics if `capacities` is empty.
+    pub fn new<I>(capacities: I, kind: Kind, partition_of: impl Fn(&K) -> usize + 'static) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let partitions: Vec<Queue<K, V>> = capacities
+            .into_iter()
+            .map(|capacity| Queue::new(capacity, kind))
+            .collect();
+        assert!(
+            !partitions.is_empty(),
+            "a partitioned cache needs a partition"
+        );
+        Self {
+            partitions,
+            partition_of: Box::new(partition_of),
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        let idx = self.partition(&key);
+        self.partitions[idx].set(key, value)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.partitions[self.partition(key)].get(key)
+    }
+
+    // Time: O(n) | Space: O(1)
+    pub fn remove(&mut self, key: &K) -> bool {
+        let idx = self.partition(key);
+        self.partitions[idx].remove(key)
+    }
+
+    /// The number of entries in partition `idx`, or `None` if there is no such partition.
+    // Time: O(1) | Space: O(1)
+    pub fn partition_len(&self, idx: usize) -> Option<usize> {
+        self.partitions.get(idx).map(|partition| partition.len())
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn partitions(&self) -> usize {
+        self.partitions.len()
+    }
+
+    // Time: O(p) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.partitions
+            .iter()
+            .map(|partition| partition.len())
+            .sum()
+    }
+
+    // Time: O(p) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.partitions.iter().all(|partition| partition.is_empty())
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn partition(&self, key: &K) -> usize {
+        (self.partition_of)(key) % self.partitions.len()
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    cmp::{Eq, Ordering},
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+};
+
+struct Slot<V, P> {
+    value: V,
+    priority: P,
+    seq: u64,
+}
+
+struct Candidate<K, P> {
+    key: K,
+    priority: P,
+    seq: u64,
+}
+
+impl<K, P: Ord> Ord for Candidate<K, P> {
+    // Reversed so the max-heap pops the lowest priority, then the oldest insertion.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl<K, P: Ord> PartialOrd for Candidate<K, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K, P: Ord> PartialEq for Candidate<K, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K, P: Ord> Eq for Candidate<K, P> {}
+
+/// The priority cache evicts the entry with the lowest caller-assigned priority,
+/// breaking ties by evicting the one inserted first. Updating an entry counts as a fresh insertion.
+/// Outdated heap candidates are skipped lazily and the heap is rebuilt when they pile up.
+pub struct PriorityCache<K, V, P> {
+    entry_map: HashMap<K, Slot<V, P>>,
+    heap: BinaryHeap<Candidate<K, P>>,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl<K, V, P> PriorityCache<K, V, P>
+where
+    K: Eq + Hash + Clone,
+    P: Ord + Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entry_map: HashMap::with_capacity(capacity),
+            heap: BinaryHeap::with_capacity(capacity),
+            capacity,
+            next_seq: 0,
+        }
+    }
+
+    // Time: O(log n) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V, priority: P) -> bool {
+        if !self.entry_map.contains_key(&key) && self.entry_map.len() >= self.capacity {
+            match self.pop_lowest() {
+                Some(_) => (),
+                None => return false,
+            }
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(Candidate {
+            key: key.clone(),
+            priority: priority.clone(),
+            seq,
+        });
+        self.entry_map.insert(
+            key,
+            Slot {
+                value,
+                priority,
+                seq,
+            },
+        );
+        if self.heap.len() > 2 * self.entry_map.len() {
+            self.rebuild_heap();
+        }
+        true
+    }
+
+    /// Changes the priority of a cached entry, which counts as a fresh insertion for tie-breaking.
+    /// Returns false if the key isn't cached.
+    // Time: O(log n) | Space: O(n)
+    pub fn set_priority(&mut self, key: &K, priority: P) -> bool {
+        let seq = self.next_seq;
+        let slot = match self.entry_map.get_mut(key) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        self.next_seq += 1;
+        slot.priority = priority.clone();
+        slot.seq = seq;
+        self.heap.push(Candidate {
+            key: key.clone(),
+            priority,
+            seq,
+        });
+        if self.heap.len() > 2 * self.entry_map.len() {
+            self.rebuild_heap();
+        }
+        true
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entry_map.get(key).map(|slot| &slot.value)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn priority(&self, key: &K) -> Option<&P> {
+        self.entry_map.get(key).map(|slot| &slot.priority)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+
+    // Time: O(log n) amortized | Space: O(1)
+    fn pop_lowest(&mut self) -> Option<(K, V)> {
+        while let Some(candidate) = self.heap.pop() {
+            let current = match self.entry_map.get(&candidate.key) {
+                Some(slot) => slot.seq == candidate.seq,
+                None => false,
+            };
+            if current {
+                return self
+                    .entry_map
+                    .remove_entry(&candidate.key)
+                    .map(|(key, slot)| (key, slot.value));
+            }
+        }
+        None
+    }
+
+    // Time: O(n) | Space: O(n)
+    fn rebuild_heap(&mut self) {
+        self.heap = self
+            .entry_map
+            .iter()
+            .map(|(key, slot)| Candidate {
+                key: key.clone(),
+                priority: slot.priority.clone(),
+                seq: slot.seq,
+            })
+            .collect();
+    }
+}
+
+/// The scored cache is a `PriorityCache` whose priorities come from a scoring function over the values,
+/// so it always evicts the entry with the lowest score. Changing a value through `update` rescores it.
+pub struct ScoredCache<K, V, P, F> {
+    inner: PriorityCache<K, V, P>,
+    score: F,
+}
+
+impl<K, V, P, F> ScoredCache<K, V, P, F>
+where
+    K: Eq + Hash + Clone,
+    P: Ord + Clone,
+    F: Fn(&V) -> P,
+{
+    pub fn new(capacity: usize, score: F) -> Self {
+        Self {
+            inner: PriorityCache::new(capacity),
+            score,
+        }
+    }
+
+    // Time: O(log n) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        let priority = (self.score)(&value);
+        self.inner.set(key, value, priority)
+    }
+
+    /// Modifies a cached value in place and rescores it. Returns false if the key isn't cached.
+    // Time: O(log n) | Space: O(n)
+    pub fn update<U>(&mut self, key: &K, update: U) -> bool
+    where
+        U: FnOnce(&mut V),
+    {
+        let priority = match self.inner.entry_map.get_mut(key) {
+            Some(slot) => {
+                update(&mut slot.value);
+                (self.score)(&slot.value)
+            }
+            None => return false,
+        };
+        self.inner.set_priority(key, priority)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.inner.get(key)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
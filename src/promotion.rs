@@ -0,0 +1,99 @@
+use crate::{
+    queue::{Kind, Queue},
+    SetOutcome,
+};
+use std::{cmp::Eq, collections::HashMap, hash::Hash};
+
+/// The promotion cache admits new entries into a probationary queue and counts their accesses.
+/// Once an entry has been read `threshold` times it moves into a protected queue,
+/// which only evicts when it is itself full. An entry evicted from the protected queue is demoted back to probation,
+/// where it starts counting accesses again. Both queues evict in FIFO order,
+/// so a burst of one-off keys churns through probation without touching promoted entries.
+pub struct PromotionCache<K, V> {
+    probation: Queue<K, V>,
+    protected: Queue<K, V>,
+    accesses: HashMap<K, u32>,
+    threshold: u32,
+}
+
+impl<K, V> PromotionCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(probation_capacity: usize, protected_capacity: usize, threshold: u32) -> Self {
+        Self {
+            probation: Queue::new(probation_capacity, Kind::FIFO),
+            protected: Queue::new(protected_capacity, Kind::FIFO),
+            accesses: HashMap::with_capacity(probation_capacity),
+            threshold,
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        if self.protected.contains_key(&key) {
+            return self.protected.set(key, value);
+        }
+        match self.probation.set_reporting(key, value) {
+            SetOutcome::Evicted(evicted_key, _) => {
+                self.accesses.remove(&evicted_key);
+                true
+            }
+            SetOutcome::Rejected => false,
+            SetOutcome::Inserted | SetOutcome::Updated => true,
+        }
+    }
+
+    // Time: O(1), O(n) when promoting | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if self.protected.contains_key(key) {
+            return self.protected.get(key);
+        }
+        if !self.probation.contains_key(key) {
+            return None;
+        }
+        let accesses = self.accesses.entry(key.clone()).or_insert(0);
+        *accesses += 1;
+        if *accesses < self.threshold {
+            return self.probation.get(key);
+        }
+        // Protected entries are never pinned, so the protected queue only rejects when it has no capacity.
+        // Checking up front keeps the entry in probation instead of dropping it on a read.
+        if self.protected.capacity() == 0 {
+            return self.probation.get(key);
+        }
+        self.accesses.remove(key);
+        let value = self.probation.take(key)?;
+        match self.protected.set_reporting(key.clone(), value) {
+            SetOutcome::Evicted(demoted_key, demoted_value) => {
+                self.demote(demoted_key, demoted_value)
+            }
+            SetOutcome::Inserted | SetOutcome::Updated | SetOutcome::Rejected => (),
+        }
+        self.protected.get(key)
+    }
+
+    // Moves an entry evicted from the protected queue back into probation.
+    // The promoted entry just left probation, so there is room for it.
+    // Time: O(1) | Space: O(1)
+    fn demote(&mut self, key: K, value: V) {
+        if let SetOutcome::Evicted(evicted_key, _) = self.probation.set_reporting(key, value) {
+            self.accesses.remove(&evicted_key);
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_protected(&self, key: &K) -> bool {
+        self.protected.contains_key(key)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.probation.len() + self.protected.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.probation.is_empty() && self.protected.is_empty()
+    }
+}
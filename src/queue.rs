@@ -1,7 +1,12 @@
+use crate::{frozen::FrozenCache, CacheEvent, CacheSignal, CapacityPolicy, SetOutcome};
 use std::{
-    cmp::Eq,
-    collections::{HashMap, VecDeque},
+    borrow::Borrow,
+    cmp::{Eq, Ordering, Reverse},
+    collections::{HashMap, HashSet, TryReserveError, VecDeque},
     hash::Hash,
+    num::NonZeroUsize,
+    ops::ControlFlow,
+    sync::mpsc::{self, Receiver, Sender},
 };
 
 /// The queue-based cache implements the FIFO and LIFO queue algorithms
@@ -12,8 +17,35 @@ pub struct Queue<K, V> {
     entry_map: HashMap<K, V>,
     keys: VecDeque<K>,
     kind: Kind,
+    capacity: usize,
+    pinned: HashSet<K>,
+    auto_grow: Option<AutoGrow>,
+    window_inserts: usize,
+    window_evictions: usize,
+    last_set_evicted: bool,
+    update_moves_to_back: bool,
+    eviction_history: VecDeque<K>,
+    eviction_history_len: usize,
+    signals: Option<Sender<CacheSignal>>,
+    at_capacity: bool,
+    eviction_paused: bool,
+    subscribers: Vec<Sender<CacheEvent<K>>>,
 }
 
+/// Opt-in policy that grows a `Queue` when it evicts too often.
+/// Once `threshold` of the last `window` inserts evicted an entry,
+/// the capacity is multiplied by `factor`, never going past `max`.
+/// A `threshold` of 0 counts as 1, so growth always takes at least one eviction,
+/// and a `factor` of 0 counts as 1, which grows the capacity by a single entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoGrow {
+    pub factor: usize,
+    pub max: usize,
+    pub threshold: usize,
+    pub window: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Kind {
     FIFO,
     LIFO,
@@ -21,52 +53,821 @@ pub enum Kind {
 
 impl<K, V> Queue<K, V>
 where
-    K: Eq + Hash + Copy,
+    K: Eq + Hash + Clone,
 {
+    /// A capacity of 0 is allowed and builds a cache that rejects every new key; `from_nonzero` rules that out.
     pub fn new(capacity: usize, kind: Kind) -> Self {
+        Self::with_expected_len(capacity, capacity, kind)
+    }
+
+    // Like `new`, but allocates room for `expected_len` entries up front instead of `capacity`.
+    pub(crate) fn with_expected_len(capacity: usize, expected_len: usize, kind: Kind) -> Self {
         Self {
-            entry_map: HashMap::with_capacity(capacity),
-            keys: VecDeque::with_capacity(capacity),
-            kind: kind,
+            entry_map: HashMap::with_capacity(expected_len),
+            keys: VecDeque::with_capacity(expected_len),
+            kind,
+            capacity,
+            pinned: HashSet::new(),
+            auto_grow: None,
+            window_inserts: 0,
+            window_evictions: 0,
+            last_set_evicted: false,
+            update_moves_to_back: false,
+            eviction_history: VecDeque::new(),
+            eviction_history_len: 0,
+            signals: None,
+            at_capacity: false,
+            eviction_paused: false,
+            subscribers: Vec::new(),
         }
     }
 
-    // Time: O(1) | Space: O(n)
+    /// Like `new`, but the capacity can't be 0, so the cache always has room for at least one entry.
+    pub fn from_nonzero(capacity: NonZeroUsize, kind: Kind) -> Self {
+        Self::new(capacity.get(), kind)
+    }
+
+    /// Like `new`, but `AtLeast` lets the cache fill every slot the map allocated instead of stopping at the requested count.
+    pub fn with_capacity_policy(policy: CapacityPolicy, kind: Kind) -> Self {
+        match policy {
+            CapacityPolicy::Exact(capacity) => Self::new(capacity, kind),
+            CapacityPolicy::AtLeast(capacity) => {
+                let mut queue = Self::new(capacity, kind);
+                queue.capacity = queue.entry_map.capacity();
+                queue
+            }
+        }
+    }
+
+    /// Seeds a cache from an existing map. Entries are inserted in the map's iteration order,
+    /// which is unspecified, so when the map is larger than `capacity` which entries survive is too.
+    // Time: O(n) | Space: O(n)
+    pub fn from_map(map: HashMap<K, V>, capacity: usize, kind: Kind) -> Self {
+        let mut queue = Self::new(capacity, kind);
+        for (key, value) in map {
+            queue.set(key, value);
+        }
+        queue
+    }
+
+    // Time: O(1), O(n) for an update with `set_update_moves_to_back` | Space: O(n)
     pub fn set(&mut self, key: K, value: V) -> bool {
-        if self.entry_map.capacity() == self.entry_map.len() {
-            let key_to_evict;
-            match self.kind {
-                Kind::FIFO => {
-                    key_to_evict = match self.keys.pop_front() {
-                        Some(front_key) => front_key,
-                        None => return false,
-                    };
-                }
-                Kind::LIFO => {
-                    key_to_evict = match self.keys.pop_back() {
-                        Some(back_key) => back_key,
-                        None => return false,
-                    };
+        !matches!(self.set_reporting(key, value), SetOutcome::Rejected)
+    }
+
+    /// Like `set`, but reports whether the key was inserted, updated or displaced another entry.
+    // Time: O(1) | Space: O(n)
+    pub fn set_reporting(&mut self, key: K, value: V) -> SetOutcome<K, V> {
+        self.set_vetoing(key, value, |_, _| false)
+    }
+
+    /// Like `set`, but an unchanged value is a no-op that doesn't move the key, even with `set_update_moves_to_back`.
+    /// Since nothing is written, the no-op sends no `Insert` event, and `last_set_evicted` reports false after it.
+    /// This is a method rather than a flag so that `set` itself doesn't need `V: PartialEq`.
+    // Time: O(1) | Space: O(n)
+    pub fn set_if_changed(&mut self, key: K, value: V) -> bool
+    where
+        V: PartialEq,
+    {
+        if self.entry_map.get(&key) == Some(&value) {
+            self.last_set_evicted = false;
+            return true;
+        }
+        self.set(key, value)
+    }
+
+    /// Like `set`, but refuses a new key while `len` is at or above `max`, even when the capacity is higher.
+    /// Updates of cached keys are always allowed, and `last_set_evicted` reports false after a refusal.
+    // Time: O(1) | Space: O(n)
+    pub fn set_checked(&mut self, key: K, value: V, max: usize) -> bool {
+        if self.len() >= max && !self.contains_key(&key) {
+            self.last_set_evicted = false;
+            return false;
+        }
+        self.set(key, value)
+    }
+
+    /// Like `set`, but skips any victim for which `veto` returns true and evicts the next one in queue order instead.
+    /// The insert is rejected when every evictable entry is vetoed.
+    // Time: O(1) without vetoes, O(n) with | Space: O(n)
+    pub fn set_with_veto<F>(&mut self, key: K, value: V, veto: F) -> bool
+    where
+        F: Fn(&K, &V) -> bool,
+    {
+        !matches!(self.set_vetoing(key, value, veto), SetOutcome::Rejected)
+    }
+
+    fn set_vetoing<F>(&mut self, key: K, value: V, veto: F) -> SetOutcome<K, V>
+    where
+        F: Fn(&K, &V) -> bool,
+    {
+        let outcome = self.insert(key, value, veto);
+        self.last_set_evicted = outcome.is_eviction();
+        self.signal_pressure();
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
+        outcome
+    }
+
+    fn insert<F>(&mut self, key: K, value: V, veto: F) -> SetOutcome<K, V>
+    where
+        F: Fn(&K, &V) -> bool,
+    {
+        if let Some(existing) = self.entry_map.get_mut(&key) {
+            *existing = value;
+            self.emit(CacheEvent::Insert(&key));
+            if self.update_moves_to_back {
+                if let Some(idx) = self.keys.iter().position(|k| *k == key) {
+                    self.keys.remove(idx);
+                    self.keys.push_back(key);
                 }
             }
+            return SetOutcome::Updated;
+        }
+        let mut evicted = None;
+        if !self.eviction_paused && self.entry_map.len() >= self.capacity {
+            let key_to_evict = match self
+                .victim_index(veto)
+                .and_then(|idx| self.keys.remove(idx))
+            {
+                Some(victim_key) => victim_key,
+                None => return SetOutcome::Rejected,
+            };
+            self.record_eviction(&key_to_evict);
             match self.entry_map.remove_entry(&key_to_evict) {
-                Some((_, _)) => (),
+                Some(entry) => evicted = Some(entry),
                 // maybe it should panic if entry not present?
-                None => return false,
+                None => return SetOutcome::Rejected,
+            }
+        }
+        self.emit(CacheEvent::Insert(&key));
+        self.entry_map.insert(key.clone(), value);
+        self.keys.push_back(key);
+        self.observe_insert(evicted.is_some());
+        match evicted {
+            Some((evicted_key, evicted_value)) => SetOutcome::Evicted(evicted_key, evicted_value),
+            None => SetOutcome::Inserted,
+        }
+    }
+
+    /// Inserts every entry and returns the entries evicted along the way, in eviction order.
+    /// Room for the iterator's lower size hint, up to the free capacity, is allocated up front.
+    // Time: O(m) | Space: O(n + m)
+    pub fn set_many_evicting<I>(&mut self, entries: I) -> Vec<(K, V)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let entries = entries.into_iter();
+        let room = entries
+            .size_hint()
+            .0
+            .min(self.capacity.saturating_sub(self.len()));
+        self.entry_map.reserve(room);
+        self.keys.reserve(room);
+        let mut evicted = Vec::new();
+        for (key, value) in entries {
+            if let SetOutcome::Evicted(evicted_key, evicted_value) = self.set_reporting(key, value)
+            {
+                evicted.push((evicted_key, evicted_value));
+            }
+        }
+        evicted
+    }
+
+    /// Inserts scored items lowest score first, so under FIFO the highest scores are evicted last.
+    /// Only the `capacity` highest-scored items are inserted; the rest are dropped.
+    // Time: O(m log m) | Space: O(m)
+    pub fn warm<I>(&mut self, items: I)
+    where
+        I: IntoIterator<Item = (K, V, u32)>,
+    {
+        let mut items: Vec<(K, V, u32)> = items.into_iter().collect();
+        items.sort_by_key(|item| Reverse(item.2));
+        items.truncate(self.capacity);
+        for (key, value, _) in items.into_iter().rev() {
+            self.set(key, value);
+        }
+    }
+
+    /// Switches between FIFO and LIFO in place. Entries keep their positions in the queue,
+    /// only the end that later evictions take from changes.
+    // Time: O(1) | Space: O(1)
+    pub fn set_kind(&mut self, kind: Kind) {
+        self.kind = kind;
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// Swaps in a new data set: keys in both keep their queue position and get the new value,
+    /// keys only in `entries` are inserted in iteration order, and keys missing from `entries` are dropped even if pinned.
+    // Time: O(n + m) | Space: O(m)
+    pub fn reload<I>(&mut self, entries: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let entries: Vec<(K, V)> = entries.into_iter().collect();
+        let incoming: HashSet<&K> = entries.iter().map(|(key, _)| key).collect();
+        self.invalidate_where(|key, _| !incoming.contains(key));
+        for (key, value) in entries {
+            match self.entry_map.get_mut(&key) {
+                Some(existing) => {
+                    *existing = value;
+                    self.emit(CacheEvent::Insert(&key));
+                }
+                None => {
+                    self.set(key, value);
+                }
+            }
+        }
+    }
+
+    /// Chooses what overwriting an existing key does to its position.
+    /// By default it keeps its place in the queue. When `moves` is true it goes to the back as if freshly inserted.
+    /// Moving a key scans the queue for it, so updates then cost O(n) instead of O(1).
+    // Time: O(1) | Space: O(1)
+    pub fn set_update_moves_to_back(&mut self, moves: bool) {
+        self.update_moves_to_back = moves;
+    }
+
+    /// Whether the most recent `set` had to evict an entry, for callers that only want a pressure signal.
+    // Time: O(1) | Space: O(1)
+    pub fn last_set_evicted(&self) -> bool {
+        self.last_set_evicted
+    }
+
+    // Time: O(n) | Space: O(1)
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.take(key).is_some()
+    }
+
+    /// Removes the entry and hands its value back to the caller.
+    // Time: O(n) | Space: O(1)
+    pub fn take(&mut self, key: &K) -> Option<V> {
+        let value = self.entry_map.remove(key)?;
+        self.pinned.remove(key);
+        if let Some(idx) = self.keys.iter().position(|k| k == key) {
+            self.keys.remove(idx);
+        }
+        self.signal_pressure();
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
+        Some(value)
+    }
+
+    /// Removes every entry for which `f` returns true, pinned or not, and returns how many were removed.
+    // Time: O(n) | Space: O(1)
+    pub fn invalidate_where<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let before = self.entry_map.len();
+        self.entry_map.retain(|key, value| !f(key, value));
+        let entry_map = &self.entry_map;
+        self.keys.retain(|key| entry_map.contains_key(key));
+        self.pinned.retain(|key| entry_map.contains_key(key));
+        self.signal_pressure();
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
+        before - self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry_map.contains_key(key)
+    }
+
+    /// Checks every key and returns whether each is cached, aligned with `keys`.
+    // Time: O(m) | Space: O(m)
+    pub fn contains_many(&self, keys: &[K]) -> Vec<bool> {
+        keys.iter()
+            .map(|key| self.entry_map.contains_key(key))
+            .collect()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.entry_map.get_key_value(key) {
+            Some((cached_key, value)) => {
+                self.emit(CacheEvent::Hit(cached_key));
+                Some(value)
+            }
+            None => {
+                self.emit(CacheEvent::Miss);
+                None
             }
         }
-        match self.entry_map.insert(key, value) {
-            Some(_) => (),
-            None => self.keys.push_back(key),
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.get(key).cloned()
+    }
+
+    /// Returns the cached value, computing and inserting it with `f` on a miss.
+    /// Returns `None` when the insertion is rejected because every entry is pinned.
+    // Time: O(1) | Space: O(n)
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> Option<&V>
+    where
+        F: FnOnce() -> V,
+    {
+        self.get_or_insert_with_status(key, f)
+            .map(|(value, _)| value)
+    }
+
+    /// Like `get_or_insert_with`, but also reports `true` for a hit and `false` when `f` computed the value.
+    // Time: O(1) | Space: O(n)
+    pub fn get_or_insert_with_status<F>(&mut self, key: K, f: F) -> Option<(&V, bool)>
+    where
+        F: FnOnce() -> V,
+    {
+        if self.entry_map.contains_key(&key) {
+            self.emit(CacheEvent::Hit(&key));
+            return self.entry_map.get(&key).map(|value| (value, true));
+        }
+        self.emit(CacheEvent::Miss);
+        if !self.set(key.clone(), f()) {
+            return None;
+        }
+        self.entry_map.get(&key).map(|value| (value, false))
+    }
+
+    /// Returns a copy of the cached value, or `V::default()` on a miss.
+    /// A miss doesn't populate the cache.
+    // Time: O(1) | Space: O(1)
+    pub fn get_or_default<Q>(&self, key: &Q) -> V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Default + Clone,
+    {
+        self.get_cloned(key).unwrap_or_default()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// How full the cache is, from 0.0 when empty to 1.0 when full. A zero capacity cache reports 0.0.
+    // Time: O(1) | Space: O(1)
+    pub fn utilization(&self) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
         }
+        self.len() as f64 / self.capacity as f64
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+
+    /// Grows the capacity by `additional` entries and allocates room for them up front,
+    /// so a bulk load neither evicts nor rehashes.
+    /// The capacity saturates at `usize::MAX`, and when the room can't be allocated,
+    /// e.g. for `usize::MAX`, the capacity still grows but nothing is allocated up front.
+    // Time: O(n) | Space: O(n)
+    pub fn reserve(&mut self, additional: usize) {
+        self.capacity = self.capacity.saturating_add(additional);
+        let _ = self
+            .entry_map
+            .try_reserve(self.capacity.saturating_sub(self.entry_map.len()));
+        let _ = self
+            .keys
+            .try_reserve(self.capacity.saturating_sub(self.keys.len()));
+        self.signal_pressure();
+    }
+
+    /// Like `reserve`, but returns the allocation error instead of aborting.
+    /// The capacity is left unchanged when it fails.
+    // Time: O(n) | Space: O(n)
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let capacity = self.capacity.saturating_add(additional);
+        self.entry_map
+            .try_reserve(capacity.saturating_sub(self.entry_map.len()))?;
+        self.keys
+            .try_reserve(capacity.saturating_sub(self.keys.len()))?;
+        self.capacity = capacity;
+        self.signal_pressure();
+        Ok(())
+    }
+
+    /// Returns the keys front to back, which is the order FIFO evicts them in.
+    /// Overwriting a key keeps its position.
+    // Time: O(n) | Space: O(n)
+    pub fn key_order(&self) -> Vec<&K> {
+        self.keys.iter().collect()
+    }
+
+    /// Returns the keys in the order the cache would evict them, whatever the `Kind`:
+    /// front to back for FIFO, back to front for LIFO. Pinned keys are left out.
+    // Time: O(n) | Space: O(n)
+    pub fn eviction_order(&self) -> Vec<&K> {
+        let evictable = self.keys.iter().filter(|key| !self.pinned.contains(*key));
+        match self.kind {
+            Kind::FIFO => evictable.collect(),
+            Kind::LIFO => evictable.rev().collect(),
+        }
+    }
+
+    /// Iterates over the entries front to back, in the same order as `key_order`.
+    // Time: O(n) | Space: O(1)
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.keys
+            .iter()
+            .filter_map(|key| self.entry_map.get_key_value(key))
+    }
+
+    /// Walks the entries front to back with mutable access to the values,
+    /// stopping as soon as `f` returns `Break`. Entries after that are left untouched.
+    // Time: O(n) | Space: O(1)
+    pub fn for_each_until<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> ControlFlow<()>,
+    {
+        for key in self.keys.iter() {
+            if let Some(value) = self.entry_map.get_mut(key) {
+                if f(key, value).is_break() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns up to `n` of the most recently inserted entries, newest first. Overwrites don't count as insertions.
+    // Time: O(n) | Space: O(n)
+    pub fn recent(&self, n: usize) -> Vec<(&K, &V)> {
+        self.keys
+            .iter()
+            .rev()
+            .take(n)
+            .filter_map(|key| self.entry_map.get_key_value(key))
+            .collect()
+    }
+
+    /// Returns up to `n` of the earliest inserted entries, oldest first.
+    // Time: O(n) | Space: O(n)
+    pub fn oldest(&self, n: usize) -> Vec<(&K, &V)> {
+        self.iter().take(n).collect()
+    }
+
+    /// Copies the entries into a read-only snapshot that iterates in queue order.
+    /// Later changes to this cache don't affect the snapshot.
+    // Time: O(n) | Space: O(n)
+    pub fn freeze(&self) -> FrozenCache<K, V>
+    where
+        V: Clone,
+    {
+        FrozenCache::from_entries(self.iter().map(|(key, value)| (key.clone(), value.clone())))
+    }
+
+    /// Like `freeze`, but consumes the cache and moves the entries instead of cloning them.
+    // Time: O(n) | Space: O(n)
+    pub fn into_frozen(mut self) -> FrozenCache<K, V> {
+        let keys = std::mem::take(&mut self.keys);
+        FrozenCache::from_entries(keys.into_iter().filter_map(|key| {
+            let value = self.entry_map.remove(&key)?;
+            Some((key, value))
+        }))
+    }
+
+    /// Collects the entries and sorts them with `cmp`, for deterministic logging and snapshots.
+    // Time: O(n log n) | Space: O(n)
+    pub fn iter_sorted_by<F>(&self, mut cmp: F) -> Vec<(&K, &V)>
+    where
+        F: FnMut(&(&K, &V), &(&K, &V)) -> Ordering,
+    {
+        let mut entries: Vec<(&K, &V)> = self.entry_map.iter().collect();
+        entries.sort_by(|a, b| cmp(a, b));
+        entries
+    }
+
+    /// Copies the current keys, for diffing against a later snapshot with `diff_keys`.
+    // Time: O(n) | Space: O(n)
+    pub fn key_snapshot(&self) -> HashSet<K> {
+        self.entry_map.keys().cloned().collect()
+    }
+
+    /// Marks a cached key as non-evictable. Returns false if the key isn't cached.
+    /// When every entry of a full cache is pinned, inserting a new key is rejected.
+    // Time: O(1) | Space: O(1)
+    pub fn pin(&mut self, key: &K) -> bool {
+        if !self.entry_map.contains_key(key) {
+            return false;
+        }
+        self.pinned.insert(key.clone());
         true
     }
 
+    /// Makes a pinned key evictable again. Returns false if the key wasn't pinned.
+    // Time: O(1) | Space: O(1)
+    pub fn unpin(&mut self, key: &K) -> bool {
+        self.pinned.remove(key)
+    }
+
+    /// Evicts the oldest unpinned entry, whatever the configured `Kind`.
+    // Time: O(1) without pins, O(n) with | Space: O(1)
+    pub fn evict_front(&mut self) -> Option<(K, V)> {
+        let idx = self.front_victim()?;
+        self.evict_at(idx)
+    }
+
+    /// Evicts the newest unpinned entry, whatever the configured `Kind`.
+    // Time: O(1) without pins, O(n) with | Space: O(1)
+    pub fn evict_back(&mut self) -> Option<(K, V)> {
+        let idx = self.back_victim()?;
+        self.evict_at(idx)
+    }
+
+    /// Sheds load for an external memory monitor: evicts `evict_fraction` of the entries in policy order
+    /// and returns how many were evicted. The fraction is clamped to `0.0..=1.0` and the count rounded up,
+    /// so any positive fraction evicts at least one entry. Pinned entries are skipped, so fewer may go.
+    // Time: O(m) without pins, O(n * m) with | Space: O(1)
+    pub fn on_memory_pressure(&mut self, evict_fraction: f64) -> usize {
+        let target = (self.len() as f64 * evict_fraction.clamp(0.0, 1.0)).ceil() as usize;
+        let mut evicted = 0;
+        while evicted < target {
+            match self.victim_index(|_, _| false) {
+                Some(idx) => {
+                    self.evict_at(idx);
+                    evicted += 1;
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    /// Stops `set` from evicting, so the cache can grow past its capacity during a batch.
+    /// Nothing bounds that growth until `resume_eviction` is called, so keep the paused section short.
     // Time: O(1) | Space: O(1)
-    pub fn get(&self, key: K) -> Option<&V> {
-        match self.entry_map.get_key_value(&key) {
-            Some((_, value)) => Some(value),
-            None => None,
+    pub fn pause_eviction(&mut self) {
+        self.eviction_paused = true;
+    }
+
+    /// Lets `set` evict again and trims the cache back to its capacity in policy order.
+    /// Returns how many entries were evicted; pinned entries can leave it over capacity.
+    // Time: O(m) without pins, O(n * m) with | Space: O(1)
+    pub fn resume_eviction(&mut self) -> usize {
+        self.eviction_paused = false;
+        let mut evicted = 0;
+        while self.entry_map.len() > self.capacity {
+            match self.victim_index(|_, _| false) {
+                Some(idx) => {
+                    self.evict_at(idx);
+                    evicted += 1;
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    // The first key from the end the policy evicts from that is neither pinned nor vetoed.
+    // Time: O(1) without pins or vetoes, O(n) with | Space: O(1)
+    fn victim_index<F>(&self, veto: F) -> Option<usize>
+    where
+        F: Fn(&K, &V) -> bool,
+    {
+        let evictable = |key: &K| {
+            !self.pinned.contains(key)
+                && match self.entry_map.get(key) {
+                    Some(value) => !veto(key, value),
+                    None => false,
+                }
+        };
+        match self.kind {
+            Kind::FIFO => self.keys.iter().position(evictable),
+            Kind::LIFO => self.keys.iter().rposition(evictable),
         }
     }
+
+    fn front_victim(&self) -> Option<usize> {
+        self.keys.iter().position(|key| !self.pinned.contains(key))
+    }
+
+    fn back_victim(&self) -> Option<usize> {
+        self.keys.iter().rposition(|key| !self.pinned.contains(key))
+    }
+
+    fn evict_at(&mut self, idx: usize) -> Option<(K, V)> {
+        let key = self.keys.remove(idx)?;
+        self.record_eviction(&key);
+        let evicted = self.entry_map.remove_entry(&key);
+        self.signal_pressure();
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
+        evicted
+    }
+
+    // How many entries the map and the key deque can hold before reallocating.
+    #[cfg(test)]
+    pub(crate) fn storage_capacity(&self) -> (usize, usize) {
+        (self.entry_map.capacity(), self.keys.capacity())
+    }
+
+    /// Panics if the map, the key deque and the pin set disagree:
+    /// every cached key must appear in the deque exactly once, and only cached keys may be pinned.
+    /// Mutating operations call this in debug builds with the `check-invariants` feature.
+    #[cfg(debug_assertions)]
+    pub fn check_invariants(&self) {
+        assert_eq!(self.keys.len(), self.entry_map.len());
+        let mut seen = HashSet::with_capacity(self.keys.len());
+        for key in &self.keys {
+            assert!(self.entry_map.contains_key(key));
+            assert!(seen.insert(key), "key appears twice in the deque");
+        }
+        assert!(self
+            .pinned
+            .iter()
+            .all(|key| self.entry_map.contains_key(key)));
+    }
+
+    /// Keeps the keys of the last `len` evicted entries, for debugging eviction decisions.
+    /// A `len` of 0, the default, turns the history off. Shrinking drops the oldest keys.
+    // Time: O(n) | Space: O(len)
+    pub fn set_eviction_history(&mut self, len: usize) {
+        self.eviction_history_len = len;
+        while self.eviction_history.len() > len {
+            self.eviction_history.pop_front();
+        }
+    }
+
+    /// Returns the most recently evicted keys, oldest eviction first.
+    /// Only evictions are recorded, not `remove` or `take`.
+    // Time: O(1) | Space: O(1)
+    pub fn recently_evicted(&self) -> impl Iterator<Item = &K> {
+        self.eviction_history.iter()
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn record_eviction(&mut self, key: &K) {
+        self.emit(CacheEvent::Evict(key));
+        if self.eviction_history_len == 0 {
+            return;
+        }
+        if self.eviction_history.len() == self.eviction_history_len {
+            self.eviction_history.pop_front();
+        }
+        self.eviction_history.push_back(key.clone());
+    }
+
+    /// Returns a receiver of the events for every later insert, hit, miss and eviction, in order.
+    /// Each subscriber gets its own copy of every event. Sets and `reload` report an `Insert`, whether the key was new or not.
+    /// Lookups through `get`, `get_cloned`, `get_or_default` and `get_or_insert_with` report hits and misses,
+    /// and evictions are reported before the insert that caused them. A clone of the cache starts without subscribers.
+    // Time: O(1) | Space: O(1)
+    pub fn subscribe(&mut self) -> Receiver<CacheEvent<K>> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    // Sends to every subscriber, skipping the ones whose receiver was dropped. Without subscribers no key is cloned.
+    // Time: O(s) | Space: O(1)
+    fn emit(&self, event: CacheEvent<&K>) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        let event = match event {
+            CacheEvent::Insert(key) => CacheEvent::Insert(key.clone()),
+            CacheEvent::Hit(key) => CacheEvent::Hit(key.clone()),
+            CacheEvent::Miss => CacheEvent::Miss,
+            CacheEvent::Evict(key) => CacheEvent::Evict(key.clone()),
+        };
+        for subscriber in &self.subscribers {
+            let _ = subscriber.send(event.clone());
+        }
+    }
+
+    /// Sends `CapacityReached` when `len` reaches the capacity and `PressureRelieved` when it drops below again.
+    /// Only changes are signalled, so a full cache that keeps evicting stays quiet.
+    /// A cache that is already full signals `CapacityReached` right away. Pass `None` to stop signalling.
+    /// The sender isn't cloned with the cache, so a clone never signals on the original's channel.
+    // Time: O(1) | Space: O(1)
+    pub fn set_signal_sender(&mut self, sender: Option<Sender<CacheSignal>>) {
+        self.signals = sender;
+        self.at_capacity = false;
+        self.signal_pressure();
+    }
+
+    // A disconnected receiver is ignored, the cache works the same without one.
+    // Time: O(1) | Space: O(1)
+    fn signal_pressure(&mut self) {
+        let sender = match &self.signals {
+            Some(sender) => sender,
+            None => return,
+        };
+        let at_capacity = self.entry_map.len() >= self.capacity;
+        if at_capacity == self.at_capacity {
+            return;
+        }
+        self.at_capacity = at_capacity;
+        let signal = if at_capacity {
+            CacheSignal::CapacityReached
+        } else {
+            CacheSignal::PressureRelieved
+        };
+        let _ = sender.send(signal);
+    }
+
+    /// Enables or disables automatic growth. Growth is bounded by `AutoGrow::max`.
+    // Time: O(1) | Space: O(1)
+    pub fn set_auto_grow(&mut self, policy: Option<AutoGrow>) {
+        self.auto_grow = policy;
+        self.window_inserts = 0;
+        self.window_evictions = 0;
+    }
+
+    fn observe_insert(&mut self, evicted: bool) {
+        let policy = match self.auto_grow {
+            Some(policy) => policy,
+            None => return,
+        };
+        self.window_inserts += 1;
+        if evicted {
+            self.window_evictions += 1;
+        }
+        if self.window_evictions >= policy.threshold.max(1) && self.capacity < policy.max {
+            let grown = self
+                .capacity
+                .saturating_mul(policy.factor.max(1))
+                .max(self.capacity + 1)
+                .min(policy.max);
+            self.reserve(grown - self.capacity);
+            self.window_inserts = 0;
+            self.window_evictions = 0;
+        } else if self.window_inserts >= policy.window {
+            self.window_inserts = 0;
+            self.window_evictions = 0;
+        }
+    }
+}
+
+impl<K, V> Clone for Queue<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            entry_map: self.entry_map.clone(),
+            keys: self.keys.clone(),
+            kind: self.kind,
+            capacity: self.capacity,
+            pinned: self.pinned.clone(),
+            auto_grow: self.auto_grow,
+            window_inserts: self.window_inserts,
+            window_evictions: self.window_evictions,
+            last_set_evicted: self.last_set_evicted,
+            update_moves_to_back: self.update_moves_to_back,
+            eviction_history: self.eviction_history.clone(),
+            eviction_history_len: self.eviction_history_len,
+            signals: None,
+            at_capacity: false,
+            eviction_paused: self.eviction_paused,
+            subscribers: Vec::new(),
+        }
+    }
+
+    // Clears and refills the existing map, deques and pin set instead of allocating new ones.
+    // The signal sender and the subscribers stay those of `self`,
+    // and the sender is told if the new entries move the cache across its capacity.
+    fn clone_from(&mut self, source: &Self) {
+        self.entry_map.clone_from(&source.entry_map);
+        self.keys.clone_from(&source.keys);
+        self.kind = source.kind;
+        self.capacity = source.capacity;
+        self.pinned.clone_from(&source.pinned);
+        self.auto_grow = source.auto_grow;
+        self.window_inserts = source.window_inserts;
+        self.window_evictions = source.window_evictions;
+        self.last_set_evicted = source.last_set_evicted;
+        self.update_moves_to_back = source.update_moves_to_back;
+        self.eviction_history.clone_from(&source.eviction_history);
+        self.eviction_history_len = source.eviction_history_len;
+        self.eviction_paused = source.eviction_paused;
+        self.signal_pressure();
+    }
 }
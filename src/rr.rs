@@ -1,10 +1,34 @@
-use rand::{seq::SliceRandom, thread_rng};
-use std::{cmp::Eq, collections::HashMap, hash::Hash};
+use crate::{frozen::FrozenCache, CapacityPolicy, SetOutcome};
+use rand::{
+    rngs::StdRng,
+    seq::{IteratorRandom, SliceRandom},
+    Rng, SeedableRng,
+};
+use std::{
+    borrow::Borrow,
+    cmp::{Eq, Ordering},
+    collections::{HashMap, HashSet, TryReserveError},
+    fmt,
+    hash::Hash,
+    num::NonZeroUsize,
+};
+
+// Called with the key, its old slot in `keys` and its new slot.
+type Reindexer<K> = Box<dyn FnMut(&K, usize, usize) + Send>;
+
+struct ReindexHook<K>(Reindexer<K>);
+
+impl<K> fmt::Debug for ReindexHook<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ReindexHook")
+    }
+}
 
 #[derive(Debug)]
 pub struct Entry<K, V> {
     map: HashMap<K, V>,
     idx: usize,
+    weight: u32,
 }
 
 impl<K, V> Entry<K, V>
@@ -15,6 +39,7 @@ where
         Self {
             map: HashMap::with_capacity(capacity),
             idx: 0,
+            weight: 1,
         }
     }
 }
@@ -22,59 +47,631 @@ where
 /// The random replacement (RR) cache algorithm,
 /// randomly selects a candidate item and discards it to make space when necessary.
 /// This algorithm does not require keeping any information about the access history.
+/// Seeding the generator with `with_seed` makes the sequence of evictions reproducible.
+/// Entries inserted with `set_weighted` are evicted with probability inversely proportional to their weight.
 #[derive(Debug)]
 pub struct RR<K, V> {
     entry_map: HashMap<K, Entry<K, V>>,
     keys: Vec<K>,
+    capacity: usize,
+    pinned: HashSet<K>,
+    last_set_evicted: bool,
+    rng: StdRng,
+    // Set by `next_eviction_candidate`; the next eviction takes it if it is still evictable.
+    pending_victim: Option<K>,
+    // Whether any entry was ever given a weight, so unweighted caches keep O(1) sampling.
+    weighted: bool,
+    on_reindex: Option<ReindexHook<K>>,
 }
 
 impl<K, V> RR<K, V>
 where
     K: Eq + Hash + Copy,
 {
+    /// A capacity of 0 is allowed and builds a cache that rejects every new key; `from_nonzero` rules that out.
     pub fn new(capacity: usize) -> Self {
+        Self::with_rng(capacity, StdRng::from_entropy())
+    }
+
+    /// Like `new`, but the capacity can't be 0, so the cache always has room for at least one entry.
+    pub fn from_nonzero(capacity: NonZeroUsize) -> Self {
+        Self::new(capacity.get())
+    }
+
+    /// Like `new`, but with a seeded generator so eviction is deterministic.
+    pub fn with_seed(capacity: usize, seed: u64) -> Self {
+        Self::with_rng(capacity, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(capacity: usize, rng: StdRng) -> Self {
         Self {
             entry_map: HashMap::with_capacity(capacity),
-            keys: Vec::new(),
+            keys: Vec::with_capacity(capacity),
+            capacity,
+            pinned: HashSet::new(),
+            last_set_evicted: false,
+            rng,
+            pending_victim: None,
+            weighted: false,
+            on_reindex: None,
+        }
+    }
+
+    /// Like `new`, but `AtLeast` lets the cache fill every slot the map allocated instead of stopping at the requested count.
+    pub fn with_capacity_policy(policy: CapacityPolicy) -> Self {
+        match policy {
+            CapacityPolicy::Exact(capacity) => Self::new(capacity),
+            CapacityPolicy::AtLeast(capacity) => {
+                let mut rr = Self::new(capacity);
+                rr.capacity = rr.entry_map.capacity();
+                rr
+            }
         }
     }
 
     /// Time: O(1) | Space: O(n)
     pub fn set(&mut self, key: K, value: V) -> bool {
+        !matches!(self.set_reporting(key, value), SetOutcome::Rejected)
+    }
+
+    /// Like `set`, but reports whether the key was inserted, updated or displaced another entry.
+    /// Time: O(1) | Space: O(n)
+    pub fn set_reporting(&mut self, key: K, value: V) -> SetOutcome<K, V> {
+        self.set_vetoing(key, value, None, |_, _| false)
+    }
+
+    /// Like `set`, but gives the entry a weight: the heavier it is, the less likely it is to be picked for eviction.
+    /// Unweighted entries weigh 1 and a weight of 0 counts as 1. Updating with `set` keeps the weight.
+    /// Once any entry is weighted, eviction samples by weight in O(n).
+    /// Time: O(n) | Space: O(n)
+    pub fn set_weighted(&mut self, key: K, value: V, weight: u32) -> bool {
+        self.weighted = true;
+        !matches!(
+            self.set_vetoing(key, value, Some(weight), |_, _| false),
+            SetOutcome::Rejected
+        )
+    }
+
+    /// Like `set`, but refuses a new key while `len` is at or above `max`, even when the capacity is higher.
+    /// Updates of cached keys are always allowed, and `last_set_evicted` reports false after a refusal.
+    /// Time: O(1) | Space: O(n)
+    pub fn set_checked(&mut self, key: K, value: V, max: usize) -> bool {
+        if self.len() >= max && !self.contains(&key) {
+            self.last_set_evicted = false;
+            return false;
+        }
+        self.set(key, value)
+    }
+
+    /// Like `set`, but re-samples the victim while `veto` returns true for it.
+    /// The insert is rejected when every unpinned entry is vetoed.
+    /// Time: O(1) without vetoes, O(n) with | Space: O(n)
+    pub fn set_with_veto<F>(&mut self, key: K, value: V, veto: F) -> bool
+    where
+        F: Fn(&K, &V) -> bool,
+    {
+        !matches!(
+            self.set_vetoing(key, value, None, veto),
+            SetOutcome::Rejected
+        )
+    }
+
+    fn set_vetoing<F>(&mut self, key: K, value: V, weight: Option<u32>, veto: F) -> SetOutcome<K, V>
+    where
+        F: Fn(&K, &V) -> bool,
+    {
+        let outcome = self.insert(key, value, weight, veto);
+        self.last_set_evicted = outcome.is_eviction();
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
+        outcome
+    }
+
+    fn insert<F>(&mut self, key: K, value: V, weight: Option<u32>, veto: F) -> SetOutcome<K, V>
+    where
+        F: Fn(&K, &V) -> bool,
+    {
         if let Some(entry) = self.entry_map.get_mut(&key) {
             entry.map.insert(key, value);
-            return true;
+            if let Some(weight) = weight {
+                entry.weight = weight.max(1);
+            }
+            return SetOutcome::Updated;
         }
-        if self.entry_map.len() == self.entry_map.capacity() {
-            let mut rng = thread_rng();
-            let rand_key = match self.keys.choose(&mut rng) {
-                Some(k) => *k,
-                None => return false,
-            };
-            let rand_entry = match self.entry_map.get_key_value(&rand_key) {
-                Some((_, entry)) => entry,
-                None => return false,
+        let mut evicted = None;
+        if self.entry_map.len() >= self.capacity {
+            let rand_idx = match self.victim_index(veto) {
+                Some(idx) => idx,
+                None => return SetOutcome::Rejected,
             };
-
-            let last_idx = self.keys.len() - 1;
-            self.keys.swap(rand_entry.idx, last_idx);
-            self.keys.pop();
-            self.entry_map.remove(&rand_key);
+            let rand_key = self.keys[rand_idx];
+            self.swap_remove_key(rand_idx);
+            evicted = self
+                .entry_map
+                .remove(&rand_key)
+                .and_then(|mut entry| entry.map.remove_entry(&rand_key));
         }
-        self.keys.push(key);
         let mut entry = Entry::new(self.entry_map.capacity());
         entry.map.insert(key, value);
-        entry.idx = self.keys.len() - 1;
+        entry.idx = self.keys.len();
+        entry.weight = weight.unwrap_or(1).max(1);
+        self.keys.push(key);
         self.entry_map.insert(key, entry);
+        match evicted {
+            Some((evicted_key, evicted_value)) => SetOutcome::Evicted(evicted_key, evicted_value),
+            None => SetOutcome::Inserted,
+        }
+    }
+
+    /// Inserts every entry and returns the entries evicted along the way, in eviction order.
+    /// Room for the iterator's lower size hint, up to the free capacity, is allocated up front.
+    /// Time: O(m) | Space: O(n + m)
+    pub fn set_many_evicting<I>(&mut self, entries: I) -> Vec<(K, V)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let entries = entries.into_iter();
+        let room = entries
+            .size_hint()
+            .0
+            .min(self.capacity.saturating_sub(self.len()));
+        self.entry_map.reserve(room);
+        self.keys.reserve(room);
+        let mut evicted = Vec::new();
+        for (key, value) in entries {
+            if let SetOutcome::Evicted(evicted_key, evicted_value) = self.set_reporting(key, value)
+            {
+                evicted.push((evicted_key, evicted_value));
+            }
+        }
+        evicted
+    }
+
+    /// Whether the most recent `set` had to evict an entry, for callers that only want a pressure signal.
+    /// Time: O(1) | Space: O(1)
+    pub fn last_set_evicted(&self) -> bool {
+        self.last_set_evicted
+    }
+
+    /// Draws the victim the next over-capacity insert will evict and returns its key.
+    /// Each call advances the generator and replaces the previous candidate.
+    /// If the candidate is removed or pinned before that insert, a fresh victim is drawn instead.
+    /// Time: O(1) without pins, O(n) with | Space: O(1)
+    pub fn next_eviction_candidate(&mut self) -> Option<&K> {
+        self.pending_victim = None;
+        let idx = self.victim_index(|_, _| false)?;
+        self.pending_victim = Some(self.keys[idx]);
+        self.keys.get(idx)
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entry_map.get(key)?;
+        entry.map.get(key)
+    }
+
+    /// Removes the entry and unpins it. Returns false if the key isn't cached.
+    /// Time: O(1) | Space: O(1)
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.take(key).is_some()
+    }
+
+    /// Removes the entry, unpins it and hands its value back to the caller.
+    /// Time: O(1) | Space: O(1)
+    pub fn take(&mut self, key: &K) -> Option<V> {
+        let mut entry = self.entry_map.remove(key)?;
+        self.swap_remove_key(entry.idx);
+        self.pinned.remove(key);
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
+        entry.map.remove(key)
+    }
+
+    /// Removes every entry for which `f` returns true, pinned or not, and returns how many were removed.
+    /// Time: O(n) | Space: O(1)
+    pub fn invalidate_where<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let before = self.entry_map.len();
+        self.entry_map
+            .retain(|key, entry| match entry.map.get(key) {
+                Some(value) => !f(key, value),
+                None => false,
+            });
+        let entry_map = &self.entry_map;
+        self.pinned.retain(|key| entry_map.contains_key(key));
+        self.compact();
+        before - self.entry_map.len()
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry_map.contains_key(key)
+    }
+
+    /// Checks every key and returns whether each is cached, aligned with `keys`.
+    /// Time: O(m) | Space: O(m)
+    pub fn contains_many(&self, keys: &[K]) -> Vec<bool> {
+        keys.iter()
+            .map(|key| self.entry_map.contains_key(key))
+            .collect()
+    }
+
+    /// Returns an owned copy of the value, for callers that cannot hold a borrow into the cache.
+    /// Time: O(1) | Space: O(1)
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        let entry = self.entry_map.get(key)?;
+        entry.map.get(key).cloned()
+    }
+
+    /// Returns a copy of the cached value, or `V::default()` on a miss.
+    /// A miss doesn't populate the cache.
+    /// Time: O(1) | Space: O(1)
+    pub fn get_or_default<Q>(&self, key: &Q) -> V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Default + Clone,
+    {
+        self.get_cloned(key).unwrap_or_default()
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// How full the cache is, from 0.0 when empty to 1.0 when full. A zero capacity cache reports 0.0.
+    /// Time: O(1) | Space: O(1)
+    pub fn utilization(&self) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+        self.len() as f64 / self.capacity as f64
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+
+    /// Grows the capacity by `additional` entries and allocates room for them up front,
+    /// so a bulk load neither evicts nor rehashes.
+    /// The capacity saturates at `usize::MAX`, and when the room can't be allocated,
+    /// e.g. for `usize::MAX`, the capacity still grows but nothing is allocated up front.
+    /// Time: O(n) | Space: O(n)
+    pub fn reserve(&mut self, additional: usize) {
+        self.capacity = self.capacity.saturating_add(additional);
+        let _ = self
+            .entry_map
+            .try_reserve(self.capacity.saturating_sub(self.entry_map.len()));
+        let _ = self
+            .keys
+            .try_reserve(self.capacity.saturating_sub(self.keys.len()));
+    }
+
+    /// Like `reserve`, but returns the allocation error instead of aborting.
+    /// The capacity is left unchanged when it fails.
+    /// Time: O(n) | Space: O(n)
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let capacity = self.capacity.saturating_add(additional);
+        self.entry_map
+            .try_reserve(capacity.saturating_sub(self.entry_map.len()))?;
+        self.keys
+            .try_reserve(capacity.saturating_sub(self.keys.len()))?;
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    /// Copies the entries into a read-only snapshot. Later changes to this cache don't affect the snapshot.
+    /// Time: O(n) | Space: O(n)
+    pub fn freeze(&self) -> FrozenCache<K, V>
+    where
+        V: Clone,
+    {
+        FrozenCache::from_entries(self.keys.iter().filter_map(|key| {
+            let entry = self.entry_map.get(key)?;
+            entry.map.get(key).map(|value| (*key, value.clone()))
+        }))
+    }
+
+    /// Like `freeze`, but consumes the cache and moves the entries instead of cloning them.
+    /// Time: O(n) | Space: O(n)
+    pub fn into_frozen(self) -> FrozenCache<K, V> {
+        FrozenCache::from_entries(
+            self.entry_map
+                .into_iter()
+                .filter_map(|(key, mut entry)| entry.map.remove_entry(&key)),
+        )
+    }
+
+    /// Collects the entries and sorts them with `cmp`, for deterministic logging and snapshots.
+    /// Time: O(n log n) | Space: O(n)
+    pub fn iter_sorted_by<F>(&self, mut cmp: F) -> Vec<(&K, &V)>
+    where
+        F: FnMut(&(&K, &V), &(&K, &V)) -> Ordering,
+    {
+        let mut entries: Vec<(&K, &V)> = self
+            .entry_map
+            .iter()
+            .filter_map(|(key, entry)| entry.map.get_key_value(key))
+            .collect();
+        entries.sort_by(|a, b| cmp(a, b));
+        entries
+    }
+
+    /// Copies the current keys, for diffing against a later snapshot with `diff_keys`.
+    /// Time: O(n) | Space: O(n)
+    pub fn key_snapshot(&self) -> HashSet<K> {
+        self.entry_map.keys().copied().collect()
+    }
+
+    /// Marks a cached key as non-evictable. Returns false if the key isn't cached.
+    /// Pinned keys still count toward `len`. When every entry of a full cache is pinned, inserting a new key is rejected.
+    /// Time: O(1) | Space: O(1)
+    pub fn pin(&mut self, key: &K) -> bool {
+        if !self.entry_map.contains_key(key) {
+            return false;
+        }
+        self.pinned.insert(*key);
         true
     }
 
+    /// Makes a pinned key evictable again. Returns false if the key wasn't pinned.
+    /// Time: O(1) | Space: O(1)
+    pub fn unpin(&mut self, key: &K) -> bool {
+        self.pinned.remove(key)
+    }
+
+    /// Sheds load for an external memory monitor: evicts `evict_fraction` of the entries at random
+    /// and returns how many were evicted. The fraction is clamped to `0.0..=1.0` and the count rounded up,
+    /// so any positive fraction evicts at least one entry. Pinned entries are skipped, so fewer may go.
+    /// Time: O(m) without pins, O(n * m) with | Space: O(1)
+    pub fn on_memory_pressure(&mut self, evict_fraction: f64) -> usize {
+        let target = (self.len() as f64 * evict_fraction.clamp(0.0, 1.0)).ceil() as usize;
+        let mut evicted = 0;
+        while evicted < target {
+            match self.victim_index(|_, _| false) {
+                Some(idx) => {
+                    let key = self.keys[idx];
+                    self.remove(&key);
+                    evicted += 1;
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    /// Registers a hook called whenever a cached key moves to a different slot in the internal key vector,
+    /// with the key, its old slot and its new slot. Removing a key other than the last one moves the last key
+    /// into the freed slot, and `compact` may move any key, so callers keeping data indexed by slot can follow along.
     /// Time: O(1) | Space: O(1)
-    pub fn get(&mut self, key: K) -> Option<&V> {
-        let entry = match self.entry_map.get(&key) {
-            Some(entry) => entry,
-            None => return None,
+    pub fn set_on_reindex<F>(&mut self, hook: F)
+    where
+        F: FnMut(&K, usize, usize) + Send + 'static,
+    {
+        self.on_reindex = Some(ReindexHook(Box::new(hook)));
+    }
+
+    /// Rebuilds `keys` from the entries and points every `Entry.idx` back at its slot.
+    /// This is a maintenance operation for running occasionally.
+    /// Time: O(n) | Space: O(1)
+    pub fn compact(&mut self) {
+        self.keys.clear();
+        for (key, entry) in self.entry_map.iter_mut() {
+            let old_idx = entry.idx;
+            entry.idx = self.keys.len();
+            if old_idx != entry.idx {
+                if let Some(ReindexHook(hook)) = self.on_reindex.as_mut() {
+                    hook(key, old_idx, entry.idx);
+                }
+            }
+            self.keys.push(*key);
+        }
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
+    }
+
+    // How many entries the map and the key vector can hold before reallocating.
+    #[cfg(test)]
+    pub(crate) fn storage_capacity(&self) -> (usize, usize) {
+        (self.entry_map.capacity(), self.keys.capacity())
+    }
+
+    // `keys` and the slot every entry's `Entry.idx` points at, so tests can check they agree.
+    #[cfg(test)]
+    pub(crate) fn slots(&self) -> (&[K], Vec<(&K, usize)>) {
+        let indices = self
+            .entry_map
+            .iter()
+            .map(|(key, entry)| (key, entry.idx))
+            .collect();
+        (&self.keys, indices)
+    }
+
+    /// Panics if `keys` and the entries disagree: every key must sit at exactly one slot
+    /// and its `Entry.idx` must point at that slot. Only cached keys may be pinned.
+    /// Mutating operations call this in debug builds with the `check-invariants` feature.
+    #[cfg(debug_assertions)]
+    pub fn check_invariants(&self) {
+        assert_eq!(self.keys.len(), self.entry_map.len());
+        for (idx, key) in self.keys.iter().enumerate() {
+            let entry = match self.entry_map.get(key) {
+                Some(entry) => entry,
+                None => panic!("slot {} holds a key that isn't cached", idx),
+            };
+            assert_eq!(entry.idx, idx, "Entry.idx doesn't point at its slot");
+            assert!(entry.map.contains_key(key));
+        }
+        assert!(self
+            .pinned
+            .iter()
+            .all(|key| self.entry_map.contains_key(key)));
+    }
+
+    // Samples a random slot whose key is neither pinned nor vetoed, preferring the pending victim.
+    // Weighted caches sample the allowed keys by inverse weight. Otherwise the first random pick is uniform over all keys;
+    // when it is refused the pick is redrawn from the keys that aren't, so this always terminates.
+    // Time: O(1) without pins or vetoes, O(n) with | Space: O(n) with pins or vetoes
+    fn victim_index<F>(&mut self, veto: F) -> Option<usize>
+    where
+        F: Fn(&K, &V) -> bool,
+    {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let pending_idx = self
+            .pending_victim
+            .take()
+            .and_then(|key| self.entry_map.get(&key))
+            .map(|entry| entry.idx);
+        let vetoed = |idx: usize| {
+            let key = &self.keys[idx];
+            if self.pinned.contains(key) {
+                return true;
+            }
+            match self.entry_map.get(key).and_then(|entry| entry.map.get(key)) {
+                Some(value) => veto(key, value),
+                None => true,
+            }
         };
-        entry.map.get(&key)
+        if let Some(idx) = pending_idx.filter(|&idx| !vetoed(idx)) {
+            return Some(idx);
+        }
+        if self.weighted {
+            let allowed: Vec<usize> = (0..self.keys.len()).filter(|&idx| !vetoed(idx)).collect();
+            let entry_map = &self.entry_map;
+            let keys = &self.keys;
+            return allowed
+                .choose_weighted(&mut self.rng, |&idx| match entry_map.get(&keys[idx]) {
+                    Some(entry) => 1.0 / entry.weight as f64,
+                    None => 0.0,
+                })
+                .ok()
+                .copied();
+        }
+        let idx = self.rng.gen_range(0..self.keys.len());
+        if !vetoed(idx) {
+            return Some(idx);
+        }
+        let allowed: Vec<usize> = (0..self.keys.len()).filter(|&idx| !vetoed(idx)).collect();
+        allowed.choose(&mut self.rng).copied()
+    }
+
+    // Removes the key at `idx` by moving the last key into its slot, then re-points the moved key's entry
+    // and reports the move to the reindex hook.
+    // Time: O(1) | Space: O(1)
+    fn swap_remove_key(&mut self, idx: usize) {
+        self.keys.swap_remove(idx);
+        if let Some(moved_key) = self.keys.get(idx) {
+            if let Some(moved_entry) = self.entry_map.get_mut(moved_key) {
+                moved_entry.idx = idx;
+            }
+            if let Some(ReindexHook(hook)) = self.on_reindex.as_mut() {
+                hook(moved_key, self.keys.len(), idx);
+            }
+        }
+    }
+}
+
+/// A lighter random replacement cache that keeps only the map.
+/// `RR` keeps a parallel key vector so it can pick a victim in O(1), at the cost of
+/// storing every key twice and fixing up indexes on each removal.
+/// `SampledRR` instead picks the victim by reservoir sampling over the map's keys,
+/// which makes each eviction O(n) but needs no extra storage or bookkeeping.
+/// It suits small caches, or caches that rarely evict, where memory matters more than eviction speed.
+#[derive(Debug)]
+pub struct SampledRR<K, V> {
+    entry_map: HashMap<K, V>,
+    capacity: usize,
+    rng: StdRng,
+}
+
+impl<K, V> SampledRR<K, V>
+where
+    K: Eq + Hash + Copy,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self::with_rng(capacity, StdRng::from_entropy())
+    }
+
+    /// Like `new`, but with a seeded generator so eviction is deterministic.
+    pub fn with_seed(capacity: usize, seed: u64) -> Self {
+        Self::with_rng(capacity, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(capacity: usize, rng: StdRng) -> Self {
+        Self {
+            entry_map: HashMap::with_capacity(capacity),
+            capacity,
+            rng,
+        }
+    }
+
+    /// Time: O(1), O(n) when it evicts | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        if !self.entry_map.contains_key(&key) && self.entry_map.len() >= self.capacity {
+            if let Some(victim) = self.entry_map.keys().copied().choose(&mut self.rng) {
+                self.entry_map.remove(&victim);
+            }
+        }
+        self.entry_map.insert(key, value);
+        true
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry_map.get(key)
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry_map.contains_key(key)
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    /// Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
     }
 }
@@ -0,0 +1,127 @@
+use crate::{
+    queue::{Kind, Queue},
+    SetOutcome,
+};
+use std::{cmp::Eq, hash::Hash};
+
+/// Which level of a `TieredCache` holds a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+    L1,
+    L2,
+}
+
+/// Lookup counters for each level of a `TieredCache`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TierStats {
+    pub l1_hits: u64,
+    pub l2_hits: u64,
+    pub misses: u64,
+}
+
+/// The tiered cache puts a small L1 queue in front of a larger L2 queue, like a CPU cache hierarchy.
+/// New entries go into L1. Entries evicted from L1 are demoted into L2,
+/// and an L2 hit promotes the entry back into L1. L1 evicts its least recently used entry,
+/// since every L1 hit moves the key to the back of the queue, so a hot key stays in L1. L2 evicts in FIFO order.
+/// With an L1 capacity of 0 nothing can be promoted, and entries live in L2 only.
+pub struct TieredCache<K, V> {
+    l1: Queue<K, V>,
+    l2: Queue<K, V>,
+    stats: TierStats,
+}
+
+impl<K, V> TieredCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(l1_capacity: usize, l2_capacity: usize) -> Self {
+        Self {
+            l1: Queue::new(l1_capacity, Kind::FIFO),
+            l2: Queue::new(l2_capacity, Kind::FIFO),
+            stats: TierStats::default(),
+        }
+    }
+
+    // Time: O(n) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        // L1 is never pinned, so it only rejects when it has no capacity.
+        // Checking up front keeps the old L2 value instead of dropping it.
+        if self.l1.capacity() == 0 {
+            return self.l2.set(key, value);
+        }
+        self.l2.remove(&key);
+        self.insert_l1(key, value)
+    }
+
+    // Time: O(n) | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if let Some(value) = self.l1.take(key) {
+            self.stats.l1_hits += 1;
+            // The key just left L1, so there is room to put it back at the end without evicting.
+            self.l1.set(key.clone(), value);
+            return self.l1.get(key);
+        }
+        if self.l1.capacity() == 0 {
+            return match self.l2.get(key) {
+                Some(value) => {
+                    self.stats.l2_hits += 1;
+                    Some(value)
+                }
+                None => {
+                    self.stats.misses += 1;
+                    None
+                }
+            };
+        }
+        match self.l2.take(key) {
+            Some(value) => {
+                self.stats.l2_hits += 1;
+                self.insert_l1(key.clone(), value);
+                self.l1.get(key)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Reports which level holds `key`, without counting as an access.
+    // Time: O(1) | Space: O(1)
+    pub fn tier(&self, key: &K) -> Option<Tier> {
+        if self.l1.contains_key(key) {
+            Some(Tier::L1)
+        } else if self.l2.contains_key(key) {
+            Some(Tier::L2)
+        } else {
+            None
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn stats(&self) -> TierStats {
+        self.stats
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.l1.len() + self.l2.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.l1.is_empty() && self.l2.is_empty()
+    }
+
+    // Inserts into L1 and demotes whatever L1 evicts into L2.
+    fn insert_l1(&mut self, key: K, value: V) -> bool {
+        match self.l1.set_reporting(key, value) {
+            SetOutcome::Evicted(demoted_key, demoted_value) => {
+                self.l2.set(demoted_key, demoted_value);
+                true
+            }
+            SetOutcome::Rejected => false,
+            SetOutcome::Inserted | SetOutcome::Updated => true,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::clock::{Clock, SystemClock};
+use std::{
+    cmp::Eq,
+    collections::HashMap,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// The timed cache keeps every entry for exactly `ttl` after it was set, however often it is read.
+/// Setting a key again restarts its ttl, and a ttl too large to represent as an `Instant`, e.g. `Duration::MAX`,
+/// means entries never expire. The number of entries is not bounded:
+/// expired entries stop being returned by `get` but stay stored, and count toward `len`, until `purge_expired`.
+pub struct TimedCache<K, V, C = SystemClock> {
+    // `None` when the expiry overflows `Instant`.
+    entry_map: HashMap<K, (V, Option<Instant>)>,
+    ttl: Duration,
+    clock: C,
+}
+
+impl<K, V> TimedCache<K, V>
+where
+    K: Eq + Hash,
+{
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_clock(ttl, SystemClock)
+    }
+}
+
+impl<K, V, C> TimedCache<K, V, C>
+where
+    K: Eq + Hash,
+    C: Clock,
+{
+    pub fn with_clock(ttl: Duration, clock: C) -> Self {
+        Self {
+            entry_map: HashMap::new(),
+            ttl,
+            clock,
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) {
+        let expires_at = self.clock.now().checked_add(self.ttl);
+        self.entry_map.insert(key, (value, expires_at));
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let now = self.clock.now();
+        match self.entry_map.get(key) {
+            Some((value, expires_at)) if Self::is_live(*expires_at, now) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Drops every expired entry and returns how many were dropped.
+    // Time: O(n) | Space: O(1)
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let before = self.entry_map.len();
+        self.entry_map
+            .retain(|_, (_, expires_at)| Self::is_live(*expires_at, now));
+        before - self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    fn is_live(expires_at: Option<Instant>, now: Instant) -> bool {
+        match expires_at {
+            Some(expires_at) => now < expires_at,
+            None => true,
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Expired entries count until they are purged.
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entry_map.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entry_map.is_empty()
+    }
+}
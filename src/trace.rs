@@ -0,0 +1,81 @@
+use crate::CachePolicy;
+use std::marker::PhantomData;
+
+/// One recorded cache operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceOp<K> {
+    Get(K),
+    Set(K),
+}
+
+/// The tracing cache wraps any cache and records the key of every `get` and `set`,
+/// so a real workload can later be replayed against other policies.
+pub struct TracingCache<K, V, C> {
+    inner: C,
+    trace: Vec<TraceOp<K>>,
+    _value: PhantomData<V>,
+}
+
+impl<K, V, C> TracingCache<K, V, C>
+where
+    K: Clone,
+    C: CachePolicy<K, V>,
+{
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            trace: Vec::new(),
+            _value: PhantomData,
+        }
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.trace.push(TraceOp::Get(key.clone()));
+        self.inner.get(key)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        self.trace.push(TraceOp::Set(key.clone()));
+        self.inner.set(key, value)
+    }
+
+    /// Returns the operations recorded so far and starts a fresh trace.
+    // Time: O(1) | Space: O(1)
+    pub fn take_trace(&mut self) -> Vec<TraceOp<K>> {
+        std::mem::take(&mut self.trace)
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+/// Replays a trace against `cache`, producing values for `Set` operations with `value_for`,
+/// and returns how many `Get` operations hit.
+// Time: O(n) | Space: O(1)
+pub fn replay<K, V, C>(
+    trace: &[TraceOp<K>],
+    cache: &mut C,
+    mut value_for: impl FnMut(&K) -> V,
+) -> usize
+where
+    K: Clone,
+    C: CachePolicy<K, V>,
+{
+    let mut hits = 0;
+    for op in trace {
+        match op {
+            TraceOp::Get(key) => {
+                if cache.get(key).is_some() {
+                    hits += 1;
+                }
+            }
+            TraceOp::Set(key) => {
+                cache.set(key.clone(), value_for(key));
+            }
+        }
+    }
+    hits
+}
@@ -0,0 +1,58 @@
+use crate::{meta::MetaCache, queue::Kind};
+use std::{cmp::Eq, hash::Hash};
+
+/// The versioned cache is a `MetaCache` whose metadata is a version number, for optimistic concurrency.
+/// Every `set` stamps the entry with a fresh version from a counter shared by all keys, so versions only grow:
+/// a key that is evicted and set again never gets an old version back.
+/// Callers keep the version from `get_versioned` and later check it with `is_current`.
+pub struct VersionedCache<K, V> {
+    entries: MetaCache<K, V, u64>,
+    next_version: u64,
+}
+
+impl<K, V> VersionedCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(capacity: usize, kind: Kind) -> Self {
+        Self {
+            entries: MetaCache::new(capacity, kind),
+            next_version: 1,
+        }
+    }
+
+    // Time: O(1) | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        let version = self.next_version;
+        self.next_version += 1;
+        self.entries.set_with_meta(key, value, version)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get_versioned(&self, key: &K) -> Option<(&V, u64)> {
+        let value = self.entries.get(key)?;
+        let version = self.entries.meta(key)?;
+        Some((value, *version))
+    }
+
+    /// Whether the key is still cached at `version`, i.e. it hasn't been set or evicted since.
+    // Time: O(1) | Space: O(1)
+    pub fn is_current(&self, key: &K, version: u64) -> bool {
+        self.entries.meta(key) == Some(&version)
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
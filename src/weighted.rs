@@ -0,0 +1,215 @@
+use crate::{
+    queue::{Kind, Queue},
+    Capacity,
+};
+use std::{cmp::Eq, hash::Hash};
+
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize>;
+
+/// The weighted cache bounds both the number of entries and their total weight, as measured by the weigher,
+/// e.g. an approximate size in bytes. Entries are evicted in FIFO or LIFO order until a new entry fits under both limits,
+/// so a single insert can evict several entries. An entry heavier than `max_weight` on its own is rejected.
+/// Updating a key counts as a fresh insertion.
+///
+/// When weighing is expensive, `with_sampling` only weighs every `sample_every`-th insert and charges the others
+/// the average sampled weight. The limits are then enforced against that estimate, while `exact_weight` still
+/// weighs every entry on demand. For values whose weights don't drift over time the estimate stays within
+/// the spread of their weights.
+///
+/// The map is sized for `max_entries` up front. When the weight budget keeps the cache well below that count,
+/// `with_expected_entries` sizes it for the expected number of entries instead.
+pub struct WeightedCache<K, V> {
+    // Every value is stored with the weight it was charged on insert.
+    entries: Queue<K, (V, usize)>,
+    kind: Kind,
+    max_entries: usize,
+    max_weight: usize,
+    weight: usize,
+    weigher: Weigher<K, V>,
+    sample_every: usize,
+    inserts: usize,
+    sampled_weight: usize,
+    samples: usize,
+}
+
+impl<K, V> WeightedCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(
+        max_entries: usize,
+        max_weight: usize,
+        kind: Kind,
+        weigher: impl Fn(&K, &V) -> usize + 'static,
+    ) -> Self {
+        Self::with_sampling(max_entries, max_weight, kind, 1, weigher)
+    }
+
+    /// Like `new`, but only weighs every `sample_every`-th insert; a `sample_every` of 0 or 1 weighs them all.
+    pub fn with_sampling(
+        max_entries: usize,
+        max_weight: usize,
+        kind: Kind,
+        sample_every: usize,
+        weigher: impl Fn(&K, &V) -> usize + 'static,
+    ) -> Self {
+        Self::build(
+            max_entries,
+            max_weight,
+            max_entries,
+            kind,
+            sample_every,
+            Box::new(weigher),
+        )
+    }
+
+    /// Bounds the cache by a single limit: `Capacity::Entries` only by the number of entries,
+    /// `Capacity::Bytes` only by their total weight.
+    pub fn with_capacity(
+        capacity: Capacity,
+        kind: Kind,
+        weigher: impl Fn(&K, &V) -> usize + 'static,
+    ) -> Self {
+        match capacity {
+            Capacity::Entries(max_entries) => Self::new(max_entries, usize::MAX, kind, weigher),
+            // There is no entry bound to size the map for, so it grows as entries come in.
+            Capacity::Bytes(max_weight) => {
+                Self::with_expected_entries(usize::MAX, max_weight, 0, kind, weigher)
+            }
+        }
+    }
+
+    /// Like `new`, but allocates room for `expected_entries` instead of `max_entries`.
+    /// `max_entries` and `max_weight` are the limits the cache enforces, while `expected_entries` is only a sizing hint:
+    /// the map grows past it when more entries fit under the limits, and never holds more than `max_entries`.
+    pub fn with_expected_entries(
+        max_entries: usize,
+        max_weight: usize,
+        expected_entries: usize,
+        kind: Kind,
+        weigher: impl Fn(&K, &V) -> usize + 'static,
+    ) -> Self {
+        Self::build(
+            max_entries,
+            max_weight,
+            expected_entries.min(max_entries),
+            kind,
+            1,
+            Box::new(weigher),
+        )
+    }
+
+    fn build(
+        max_entries: usize,
+        max_weight: usize,
+        expected_entries: usize,
+        kind: Kind,
+        sample_every: usize,
+        weigher: Weigher<K, V>,
+    ) -> Self {
+        Self {
+            entries: Queue::with_expected_len(max_entries, expected_entries, kind),
+            kind,
+            max_entries,
+            max_weight,
+            weight: 0,
+            weigher,
+            sample_every: sample_every.max(1),
+            inserts: 0,
+            sampled_weight: 0,
+            samples: 0,
+        }
+    }
+
+    // Time: O(1) per evicted entry, O(n) for an update | Space: O(n)
+    pub fn set(&mut self, key: K, value: V) -> bool {
+        let weight = self.charge(&key, &value);
+        if weight > self.max_weight || self.max_entries == 0 {
+            return false;
+        }
+        if let Some((_, previous_weight)) = self.entries.take(&key) {
+            self.weight -= previous_weight;
+        }
+        while self.entries.len() >= self.max_entries || self.exceeds_max_weight(weight) {
+            let evicted = match self.kind {
+                Kind::FIFO => self.entries.evict_front(),
+                Kind::LIFO => self.entries.evict_back(),
+            };
+            match evicted {
+                Some((_, (_, evicted_weight))) => self.weight -= evicted_weight,
+                None => return false,
+            }
+        }
+        self.weight += weight;
+        self.entries.set(key, (value, weight))
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    /// The total weight of the cached entries, estimated when sampling.
+    // Time: O(1) | Space: O(1)
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// The total weight of the cached entries, weighing every one of them now.
+    // Time: O(n) | Space: O(1)
+    pub fn exact_weight(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(key, (value, _))| (self.weigher)(key, value))
+            .sum()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn max_weight(&self) -> usize {
+        self.max_weight
+    }
+
+    // How many entries the map and the key storage can hold before reallocating.
+    #[cfg(test)]
+    pub(crate) fn storage_capacity(&self) -> (usize, usize) {
+        self.entries.storage_capacity()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Time: O(1) | Space: O(1)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Whether adding `weight` would go over `max_weight`. A total that overflows is over any budget.
+    // Time: O(1) | Space: O(1)
+    fn exceeds_max_weight(&self, weight: usize) -> bool {
+        match self.weight.checked_add(weight) {
+            Some(total) => total > self.max_weight,
+            None => true,
+        }
+    }
+
+    // The weigher's result on sampled inserts, the average sampled weight on the rest.
+    // Time: O(1) | Space: O(1)
+    fn charge(&mut self, key: &K, value: &V) -> usize {
+        let sampled = self.samples == 0 || self.inserts % self.sample_every == 0;
+        self.inserts = self.inserts.wrapping_add(1);
+        if !sampled {
+            return self.sampled_weight / self.samples;
+        }
+        let weight = (self.weigher)(key, value);
+        self.sampled_weight = self.sampled_weight.saturating_add(weight);
+        self.samples += 1;
+        weight
+    }
+}